@@ -0,0 +1,115 @@
+//! Commlib-derive: derive macros for commlib
+//!
+//! ```rust, ignore
+//! use commlib::Event;
+//!
+//! #[derive(Event)]
+//! #[event(namespace = "login", id = 1001)]
+//! pub struct PlayerLogin {
+//!     pub uid: u64,
+//! }
+//! ```
+//!
+//! `namespace` 必填，`id` 省略时由 "namespace_TypeName" 哈希得到。
+//!
+//! # 重复 id 的检查需要手动开启
+//!
+//! derive 只能看到当前类型，**不会自动检查**其他类型的 id：不同模块里 (namespace, id)
+//! 相同的两个事件类型可以正常编译。需要在 crate 里用 `commlib::assert_unique_event_ids!`
+//! 列出所有事件类型（`impl_event_for!` 的也可以列出），重复时编译失败；没有列出的类型不检查。
+//!
+//! ```rust, ignore
+//! commlib::assert_unique_event_ids!(PlayerLogin, PlayerLogout, PlayerLevelUp);
+//! ```
+
+use proc_macro::TokenStream;
+use proc_macro2::{Span, TokenStream as TokenStream2};
+use quote::quote;
+use syn::{parse_macro_input, DeriveInput, LitInt, LitStr};
+
+/// Derive `commlib::Event`
+///
+/// 不会自动检查重复的 id，见 crate 文档里的 `assert_unique_event_ids!`。
+#[proc_macro_derive(Event, attributes(event))]
+pub fn derive_event(input: TokenStream) -> TokenStream {
+    let input = parse_macro_input!(input as DeriveInput);
+    match expand_event(&input) {
+        Ok(ts) => ts.into(),
+        Err(e) => e.to_compile_error().into(),
+    }
+}
+
+fn expand_event(input: &DeriveInput) -> syn::Result<TokenStream2> {
+    if !input.generics.params.is_empty() {
+        return Err(syn::Error::new_spanned(
+            &input.generics,
+            "#[derive(Event)] does not support generic types",
+        ));
+    }
+
+    let mut namespace: Option<LitStr> = None;
+    let mut id: Option<LitInt> = None;
+    for attr in &input.attrs {
+        if !attr.path().is_ident("event") {
+            continue;
+        }
+        attr.parse_nested_meta(|meta| {
+            if meta.path.is_ident("namespace") {
+                namespace = Some(meta.value()?.parse()?);
+                Ok(())
+            } else if meta.path.is_ident("id") {
+                id = Some(meta.value()?.parse()?);
+                Ok(())
+            } else {
+                Err(meta.error("unsupported event attribute, expected `namespace` or `id`"))
+            }
+        })?;
+    }
+
+    let namespace = namespace.ok_or_else(|| {
+        syn::Error::new(
+            Span::call_site(),
+            "missing #[event(namespace = \"...\")] attribute",
+        )
+    })?;
+    let ns = namespace.value();
+    if ns.is_empty() || !ns.chars().all(|c| c.is_ascii_alphanumeric() || c == '_') {
+        return Err(syn::Error::new_spanned(
+            &namespace,
+            "event namespace must be a non-empty identifier-like string",
+        ));
+    }
+
+    let ident = &input.ident;
+    let name = format!("{}_{}", ns, ident);
+    let numeric_id = match &id {
+        Some(lit) => lit.base10_parse::<u32>()?,
+        None => event_id_hash(&name),
+    };
+
+    Ok(quote! {
+        impl ::commlib::Event for #ident {
+            const EVENT_NAMESPACE: &'static str = #ns;
+            const EVENT_NAME: &'static str = #name;
+            const EVENT_ID: u32 = #numeric_id;
+
+            fn listener() -> &'static ::std::thread::LocalKey<::std::cell::RefCell<::commlib::EventListener<Self>>> {
+                ::std::thread_local! {
+                    static LISTENER: ::std::cell::RefCell<::commlib::EventListener<#ident>> =
+                        ::std::cell::RefCell::new(::commlib::EventListener::new_registered());
+                }
+                &LISTENER
+            }
+        }
+    })
+}
+
+/// FNV-1a 32, same as `commlib::event_id_hash`
+fn event_id_hash(name: &str) -> u32 {
+    let mut hash: u32 = 0x811c_9dc5;
+    for b in name.bytes() {
+        hash ^= b as u32;
+        hash = hash.wrapping_mul(0x0100_0193);
+    }
+    hash
+}
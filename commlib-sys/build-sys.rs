@@ -47,7 +47,12 @@ fn main() -> miette::Result<()> {
         manifest_path.join("cpp/signal_bindings.cc"),
         manifest_path.join("cpp/crypto/blowfish.cc"),
         manifest_path.join("cpp/crypto/blowfish_cfb64.cc"),
+        manifest_path.join("cpp/hash/crc32.cc"),
+        manifest_path.join("cpp/hash/keccak.cc"),
         manifest_path.join("cpp/hash/md5.cc"),
+        manifest_path.join("cpp/hash/sha1.cc"),
+        manifest_path.join("cpp/hash/sha256.cc"),
+        manifest_path.join("cpp/hash/sha3.cc"),
     ];
 
     // Cxx
@@ -1,22 +1,49 @@
 #include "crypto_bindings.h"
 
+#include <stdexcept>
+#include <string>
+
 #include "crypto/blowfish.h"
+#include "commlib-sys/ffi/crypto.rs.h"
 
 namespace commlib
 {
-	BlowfishCfb64::BlowfishCfb64(): m_inner_bf(), m_inner(m_inner_bf) {
+	BlowfishCfb64::BlowfishCfb64(): m_inner_bf(), m_inner(m_inner_bf), m_keyed(false), m_feedback(0), m_num(0) {
+
+	}
 
+	void BlowfishCfb64::checkKeyed() const {
+		if (!m_keyed) {
+			throw std::logic_error("blowfish key is not set");
+		}
 	}
 
 	void BlowfishCfb64::setKey(rust::Slice<const uint8_t> key) {
+		// key length range is defined once on the rust side of the bridge
+		const size_t key_min = static_cast<size_t>(BlowfishKeyLen::Min);
+		const size_t key_max = static_cast<size_t>(BlowfishKeyLen::Max);
+		if (key.length() < key_min || key.length() > key_max) {
+			throw std::invalid_argument("blowfish key length " + std::to_string(key.length())
+				+ " is out of range [" + std::to_string(key_min) + ", " + std::to_string(key_max) + "]");
+		}
+
+		// set_key() mixes into the current boxes, so start over from the initial state
+		if (m_keyed) {
+			m_inner_bf.reinitialize();
+		}
 		m_inner_bf.set_key((const char*)key.data(), key.length());
+		m_keyed = true;
 	}
 
 	void BlowfishCfb64::setInitVec(const uint64_t init_vec) {
 		m_inner.set_init_vector(init_vec);
+		m_feedback = init_vec;
+		m_num = 0;
 	}
 
 	rust::Vec<uint8_t> BlowfishCfb64::encrypt(rust::Slice<const uint8_t> data) {
+		checkKeyed();
+
 		rust::Vec<uint8_t> v;
 		v.reserve(data.size());
 		std::copy(data.begin(), data.end(), std::back_inserter(v));
@@ -25,6 +52,8 @@ namespace commlib
 	}
 
 	rust::Vec<uint8_t> BlowfishCfb64::decrypt(rust::Slice<const uint8_t> data) {
+		checkKeyed();
+
 		rust::Vec<uint8_t> v;
 		v.reserve(data.size());
 		std::copy(data.begin(), data.end(), std::back_inserter(v));
@@ -32,6 +61,44 @@ namespace commlib
 		return v;
 	}
 
+	// byte n of the feedback register, big-endian
+	static inline uint8_t feedback_byte(uint64_t feedback, size_t n) {
+		return static_cast<uint8_t>(feedback >> ((CBlowfishCfb64::REMAINDER_BASE - n) * CBlowfishCfb64::BYTE_SHIFT));
+	}
+
+	static inline uint64_t feedback_set_byte(uint64_t feedback, size_t n, uint8_t c) {
+		const size_t shift = (CBlowfishCfb64::REMAINDER_BASE - n) * CBlowfishCfb64::BYTE_SHIFT;
+		return (feedback & ~(CBlowfishCfb64::BYTE_MASK << shift)) | (static_cast<uint64_t>(c) << shift);
+	}
+
+	void BlowfishCfb64::encryptInPlace(rust::Slice<uint8_t> data) {
+		checkKeyed();
+
+		for (auto& b : data) {
+			if (0 == m_num) {
+				m_feedback = m_inner_bf.encrypt64(m_feedback);
+			}
+			const uint8_t c = b ^ feedback_byte(m_feedback, m_num);
+			m_feedback = feedback_set_byte(m_feedback, m_num, c);
+			b = c;
+			m_num = (m_num + 1) % CBlowfishCfb64::BLOCK_SIZE;
+		}
+	}
+
+	void BlowfishCfb64::decryptInPlace(rust::Slice<uint8_t> data) {
+		checkKeyed();
+
+		for (auto& b : data) {
+			if (0 == m_num) {
+				m_feedback = m_inner_bf.encrypt64(m_feedback);
+			}
+			const uint8_t c = b;
+			b = c ^ feedback_byte(m_feedback, m_num);
+			m_feedback = feedback_set_byte(m_feedback, m_num, c);
+			m_num = (m_num + 1) % CBlowfishCfb64::BLOCK_SIZE;
+		}
+	}
+
 	////
 	std::shared_ptr<BlowfishCfb64> new_blowfish() {
 		return std::make_shared<BlowfishCfb64>();
@@ -53,4 +120,12 @@ namespace commlib
 		return bf->decrypt(data);
 	}
 
+	void blowfish_encrypt_in_place(std::shared_ptr<BlowfishCfb64> bf, rust::Slice<uint8_t> data) {
+		return bf->encryptInPlace(data);
+	}
+
+	void blowfish_decrypt_in_place(std::shared_ptr<BlowfishCfb64> bf, rust::Slice<uint8_t> data) {
+		return bf->decryptInPlace(data);
+	}
+
 }
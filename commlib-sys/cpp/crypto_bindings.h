@@ -17,9 +17,20 @@ namespace commlib
         rust::Vec<uint8_t> encrypt(rust::Slice<const uint8_t> data);
         rust::Vec<uint8_t> decrypt(rust::Slice<const uint8_t> data);
 
+        // stream mode: feedback register and byte offset are kept across calls
+        void encryptInPlace(rust::Slice<uint8_t> data);
+        void decryptInPlace(rust::Slice<uint8_t> data);
+
     private:
+        // @throws std::logic_error
+        void checkKeyed() const;
+
         ::CBlowfish m_inner_bf;
         ::CBlowfishCfb64 m_inner;
+        bool m_keyed;
+
+        uint64_t m_feedback;
+        size_t m_num;
     };
 
 	//// Blowfish
@@ -28,6 +39,8 @@ namespace commlib
     void blowfish_set_init_vec(std::shared_ptr<BlowfishCfb64> bf, uint64_t init_vec);
     rust::Vec<uint8_t> blowfish_encrypt(std::shared_ptr<BlowfishCfb64> bf, rust::Slice<const uint8_t> data);
     rust::Vec<uint8_t> blowfish_decrypt(std::shared_ptr<BlowfishCfb64> bf, rust::Slice<const uint8_t> data);
+    void blowfish_encrypt_in_place(std::shared_ptr<BlowfishCfb64> bf, rust::Slice<uint8_t> data);
+    void blowfish_decrypt_in_place(std::shared_ptr<BlowfishCfb64> bf, rust::Slice<uint8_t> data);
 
 } // namespace commlib
 
@@ -1,5 +1,11 @@
 #include "hash_bindings.h"
+#include "hash/crc32.h"
+#include "hash/hmac.h"
+#include "hash/keccak.h"
 #include "hash/md5.h"
+#include "hash/sha1.h"
+#include "hash/sha256.h"
+#include "hash/sha3.h"
 
 namespace commlib
 {
@@ -18,4 +24,165 @@ namespace commlib
 	{
 		return MD5::hashBytes();
 	}
+
+	////
+	rust::String sha1(rust::Slice<const uint8_t> data)
+	{
+		SHA1 sha1;
+		return sha1(data.data(), data.length());
+	}
+
+	size_t sha1_block_size()
+	{
+		return SHA1::BlockSize;
+	}
+
+	size_t sha1_hash_bytes()
+	{
+		return SHA1::HashBytes;
+	}
+
+	////
+	rust::String sha256(rust::Slice<const uint8_t> data)
+	{
+		SHA256 sha256;
+		return sha256(data.data(), data.length());
+	}
+
+	size_t sha256_block_size()
+	{
+		return SHA256::BlockSize;
+	}
+
+	size_t sha256_hash_bytes()
+	{
+		return SHA256::HashBytes;
+	}
+
+	////
+	static SHA3::Bits to_sha3_bits(uint32_t bits)
+	{
+		switch (bits)
+		{
+		case 224: return SHA3::Bits224;
+		case 384: return SHA3::Bits384;
+		case 512: return SHA3::Bits512;
+		default:  return SHA3::Bits256;
+		}
+	}
+
+	rust::String sha3(rust::Slice<const uint8_t> data, uint32_t bits)
+	{
+		SHA3 sha3(to_sha3_bits(bits));
+		return sha3(data.data(), data.length());
+	}
+
+	////
+	static Keccak::Bits to_keccak_bits(uint32_t bits)
+	{
+		switch (bits)
+		{
+		case 224: return Keccak::Keccak224;
+		case 384: return Keccak::Keccak384;
+		case 512: return Keccak::Keccak512;
+		default:  return Keccak::Keccak256;
+		}
+	}
+
+	rust::String keccak(rust::Slice<const uint8_t> data, uint32_t bits)
+	{
+		Keccak keccak(to_keccak_bits(bits));
+		return keccak(data.data(), data.length());
+	}
+
+	////
+	rust::String crc32(rust::Slice<const uint8_t> data)
+	{
+		CRC32 crc32;
+		return crc32(data.data(), data.length());
+	}
+
+	size_t crc32_hash_bytes()
+	{
+		return CRC32::HashBytes;
+	}
+
+	////
+	rust::String hmac_md5(rust::Slice<const uint8_t> data, rust::Slice<const uint8_t> key)
+	{
+		return hmac<MD5>(data.data(), data.length(), key.data(), key.length());
+	}
+
+	rust::String hmac_sha1(rust::Slice<const uint8_t> data, rust::Slice<const uint8_t> key)
+	{
+		return hmac<SHA1>(data.data(), data.length(), key.data(), key.length());
+	}
+
+	rust::String hmac_sha256(rust::Slice<const uint8_t> data, rust::Slice<const uint8_t> key)
+	{
+		return hmac<SHA256>(data.data(), data.length(), key.data(), key.length());
+	}
+
+	//// Hasher
+	std::shared_ptr<Hasher> new_md5_hasher()
+	{
+		return std::make_shared<HasherImpl<MD5>>();
+	}
+
+	std::shared_ptr<Hasher> new_sha1_hasher()
+	{
+		return std::make_shared<HasherImpl<SHA1>>();
+	}
+
+	std::shared_ptr<Hasher> new_sha256_hasher()
+	{
+		return std::make_shared<HasherImpl<SHA256>>();
+	}
+
+	std::shared_ptr<Hasher> new_sha3_hasher(uint32_t bits)
+	{
+		return std::make_shared<HasherImpl<SHA3>>(to_sha3_bits(bits));
+	}
+
+	std::shared_ptr<Hasher> new_keccak_hasher(uint32_t bits)
+	{
+		return std::make_shared<HasherImpl<Keccak>>(to_keccak_bits(bits));
+	}
+
+	std::shared_ptr<Hasher> new_crc32_hasher()
+	{
+		return std::make_shared<HasherImpl<CRC32>>();
+	}
+
+	void hasher_update(std::shared_ptr<Hasher> h, rust::Slice<const uint8_t> data)
+	{
+		h->update(data);
+	}
+
+	rust::String hasher_finalize_hex(std::shared_ptr<Hasher> h)
+	{
+		return h->getHash();
+	}
+
+	static uint8_t hex_to_nibble(char c)
+	{
+		return (c >= 'a') ? (c - 'a' + 10) : (c >= 'A') ? (c - 'A' + 10) : (c - '0');
+	}
+
+	rust::Vec<uint8_t> hasher_finalize_bytes(std::shared_ptr<Hasher> h)
+	{
+		std::string hex = h->getHash();
+		rust::Vec<uint8_t> v;
+		v.reserve(hex.size() / 2);
+		for (size_t i = 0; i + 1 < hex.size(); i += 2)
+		{
+			v.push_back((hex_to_nibble(hex[i]) << 4) | hex_to_nibble(hex[i + 1]));
+		}
+		return v;
+	}
+
+	void hasher_reset(std::shared_ptr<Hasher> h)
+	{
+		h->reset();
+	}
 }
@@ -1,14 +1,80 @@
 #ifndef __HASH_BINDINGS_H__
 #define __HASH_BINDINGS_H__
 
+#include <memory>
+#include <string>
+#include <utility>
 #include "rust/cxx.h"
 
 namespace commlib
 {
+    /// streaming hasher, see hash/hash.h
+    class Hasher {
+    public:
+        virtual ~Hasher() = default;
+
+        virtual void update(rust::Slice<const uint8_t> data) = 0;
+        virtual std::string getHash() = 0;
+        virtual void reset() = 0;
+    };
+
+    template <typename HashMethod>
+    class HasherImpl : public Hasher {
+    public:
+        template <typename... Args>
+        explicit HasherImpl(Args&&... args) : m_inner(std::forward<Args>(args)...) {}
+
+        void update(rust::Slice<const uint8_t> data) override {
+            m_inner.add(data.data(), data.length());
+        }
+
+        std::string getHash() override {
+            return m_inner.getHash();
+        }
+
+        void reset() override {
+            m_inner.reset();
+        }
+
+    private:
+        HashMethod m_inner;
+    };
+
     rust::String md5(rust::Slice<const uint8_t> data);
     size_t md5_block_size();
     size_t md5_hash_bytes();
 
+    rust::String sha1(rust::Slice<const uint8_t> data);
+    size_t sha1_block_size();
+    size_t sha1_hash_bytes();
+
+    rust::String sha256(rust::Slice<const uint8_t> data);
+    size_t sha256_block_size();
+    size_t sha256_hash_bytes();
+
+    rust::String sha3(rust::Slice<const uint8_t> data, uint32_t bits);
+    rust::String keccak(rust::Slice<const uint8_t> data, uint32_t bits);
+
+    rust::String crc32(rust::Slice<const uint8_t> data);
+    size_t crc32_hash_bytes();
+
+    rust::String hmac_md5(rust::Slice<const uint8_t> data, rust::Slice<const uint8_t> key);
+    rust::String hmac_sha1(rust::Slice<const uint8_t> data, rust::Slice<const uint8_t> key);
+    rust::String hmac_sha256(rust::Slice<const uint8_t> data, rust::Slice<const uint8_t> key);
+
+    //// Hasher
+    std::shared_ptr<Hasher> new_md5_hasher();
+    std::shared_ptr<Hasher> new_sha1_hasher();
+    std::shared_ptr<Hasher> new_sha256_hasher();
+    std::shared_ptr<Hasher> new_sha3_hasher(uint32_t bits);
+    std::shared_ptr<Hasher> new_keccak_hasher(uint32_t bits);
+    std::shared_ptr<Hasher> new_crc32_hasher();
+
+    void hasher_update(std::shared_ptr<Hasher> h, rust::Slice<const uint8_t> data);
+    rust::String hasher_finalize_hex(std::shared_ptr<Hasher> h);
+    rust::Vec<uint8_t> hasher_finalize_bytes(std::shared_ptr<Hasher> h);
+    void hasher_reset(std::shared_ptr<Hasher> h);
+
 } // namespace commlib
 
 #endif // __HASH_BINDINGS_H__
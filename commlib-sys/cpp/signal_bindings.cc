@@ -1,23 +1,114 @@
 #include "signal_bindings.h"
-#include <signal.h>  // for signal, SIGABRT, SIGINT, SIGPIPE
+#include <signal.h>  // for sigaction, SIGABRT, SIGINT, SIGPIPE
+#include <errno.h>
+#include <string.h>
+
+#include <stdexcept>
+#include <string>
 
 namespace commlib
 {
+	typedef void (*SignalHandler)(int);
+
+	static void throw_signal_error(const char *op, int32_t signo)
+	{
+		throw std::runtime_error(std::string(op) + " failed for signal " + std::to_string(signo) + ": " + strerror(errno));
+	}
+
+	static void set_signal_handler(int32_t signo, SignalHandler handler)
+	{
+#ifdef _WIN32
+		if (signal(signo, handler) == SIG_ERR) {
+			throw_signal_error("signal", signo);
+		}
+#else
+		struct sigaction sa;
+		memset(&sa, 0, sizeof(sa));
+		sa.sa_handler = handler;
+		sigemptyset(&sa.sa_mask);
+		sa.sa_flags = SA_RESTART; // restart interrupted system calls
+
+		if (sigaction(signo, &sa, nullptr) != 0) {
+			throw_signal_error("sigaction", signo);
+		}
+#endif
+	}
+
 	void init_signal_handlers(SignalCallback cb_ctrl_c, SignalCallback cb_usr1, SignalCallback cb_usr2)
 	{
-		signal(SIGINT, cb_ctrl_c);
-		signal(SIGTERM, cb_ctrl_c);
-		signal(SIGABRT, cb_ctrl_c);
+		set_signal_handler(SIGINT, cb_ctrl_c);
+		set_signal_handler(SIGTERM, cb_ctrl_c);
+		set_signal_handler(SIGABRT, cb_ctrl_c);
+
+#ifdef _WIN32
+		set_signal_handler(SIGBREAK, cb_ctrl_c);
+#else
+		set_signal_handler(SIGQUIT, cb_ctrl_c);
+
+		set_signal_handler(SIGPIPE, SIG_IGN); // ignore signal
+
+		set_signal_handler(SIGUSR1, cb_usr1); // 关服
+		set_signal_handler(SIGUSR2, cb_usr2); // 热更新配置
+#endif
+	}
+
+	void install_signal_handler(int32_t signo, SignalCallback cb)
+	{
+		set_signal_handler(signo, cb);
+	}
+
+	void ignore_signal(int32_t signo)
+	{
+		set_signal_handler(signo, SIG_IGN);
+	}
 
+	void restore_default_signal(int32_t signo)
+	{
+		set_signal_handler(signo, SIG_DFL);
+	}
+
+	int32_t query_signal_disposition(int32_t signo)
+	{
+		SignalHandler handler;
 #ifdef _WIN32
-		signal(SIGBREAK, cb_ctrl_c);
+		// no query api on windows: swap in the default and put the old one back
+		handler = signal(signo, SIG_DFL);
+		if (handler == SIG_ERR) {
+			throw_signal_error("signal", signo);
+		}
+		signal(signo, handler);
 #else
-		signal(SIGQUIT, cb_ctrl_c);
+		struct sigaction sa;
+		if (sigaction(signo, nullptr, &sa) != 0) {
+			throw_signal_error("sigaction", signo);
+		}
+		handler = sa.sa_handler;
+#endif
 
-		signal(SIGPIPE, SIG_IGN); // ignore signal
+		if (handler == SIG_DFL) {
+			return SIGNAL_DISPOSITION_DEFAULT;
+		}
+		else if (handler == SIG_IGN) {
+			return SIGNAL_DISPOSITION_IGNORE;
+		}
+		return SIGNAL_DISPOSITION_HANDLER;
+	}
 
-		signal(SIGUSR1, cb_usr1); // 关服
-		signal(SIGUSR2, cb_usr2); // 热更新配置
+	int32_t signal_rtmin()
+	{
+#ifdef SIGRTMIN
+		return SIGRTMIN;
+#else
+		return -1;
+#endif
+	}
+
+	int32_t signal_rtmax()
+	{
+#ifdef SIGRTMAX
+		return SIGRTMAX;
+#else
+		return -1;
 #endif
 	}
 }
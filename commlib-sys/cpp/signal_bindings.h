@@ -7,7 +7,20 @@ using SignalCallback = void(*)(int32_t);
 
 namespace commlib
 {
+    // signal dispositions returned by query_signal_disposition
+    const int32_t SIGNAL_DISPOSITION_DEFAULT = 0;
+    const int32_t SIGNAL_DISPOSITION_IGNORE = 1;
+    const int32_t SIGNAL_DISPOSITION_HANDLER = 2;
+
     void init_signal_handlers(SignalCallback cb_ctrl_c, SignalCallback cb_usr1, SignalCallback cb_usr2);
+
+    void install_signal_handler(int32_t signo, SignalCallback cb);
+    void ignore_signal(int32_t signo);
+    void restore_default_signal(int32_t signo);
+    int32_t query_signal_disposition(int32_t signo);
+
+    int32_t signal_rtmin();
+    int32_t signal_rtmax();
 } // namespace commlib
 
 #endif // __SIGNAL_BINDINGS_H__
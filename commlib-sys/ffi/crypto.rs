@@ -1,6 +1,14 @@
 #[cxx::bridge]
 pub mod ffi_crypto {
 
+    /// Blowfish key length range in bytes (32 ~ 448 bits), shared with crypto_bindings.cc
+    #[namespace = "commlib"]
+    #[repr(u32)]
+    enum BlowfishKeyLen {
+        Min = 4,
+        Max = 56,
+    }
+
     unsafe extern "C++" {
         include!("crypto_bindings.h");
 
@@ -8,18 +16,24 @@ pub mod ffi_crypto {
         type BlowfishCfb64;
 
         #[namespace = "commlib"]
-        fn new_blowfish() -> SharedPtr<BlowfishCfb64>;
+        fn new_blowfish() -> Result<SharedPtr<BlowfishCfb64>>;
 
         #[namespace = "commlib"]
-        fn blowfish_set_key(bf: SharedPtr<BlowfishCfb64>, key: &[u8]);
+        fn blowfish_set_key(bf: SharedPtr<BlowfishCfb64>, key: &[u8]) -> Result<()>;
 
         #[namespace = "commlib"]
         fn blowfish_set_init_vec(bf: SharedPtr<BlowfishCfb64>, init_vec: u64);
 
         #[namespace = "commlib"]
-        fn blowfish_encrypt(bf: SharedPtr<BlowfishCfb64>, data: &[u8]) -> Vec<u8>;
+        fn blowfish_encrypt(bf: SharedPtr<BlowfishCfb64>, data: &[u8]) -> Result<Vec<u8>>;
+
+        #[namespace = "commlib"]
+        fn blowfish_decrypt(bf: SharedPtr<BlowfishCfb64>, data: &[u8]) -> Result<Vec<u8>>;
+
+        #[namespace = "commlib"]
+        fn blowfish_encrypt_in_place(bf: SharedPtr<BlowfishCfb64>, data: &mut [u8]) -> Result<()>;
 
         #[namespace = "commlib"]
-        fn blowfish_decrypt(bf: SharedPtr<BlowfishCfb64>, data: &[u8]) -> Vec<u8>;
+        fn blowfish_decrypt_in_place(bf: SharedPtr<BlowfishCfb64>, data: &mut [u8]) -> Result<()>;
     }
 }
@@ -4,6 +4,9 @@ pub mod ffi_hash {
     unsafe extern "C++" {
         include!("hash_bindings.h");
 
+        #[namespace = "commlib"]
+        type Hasher;
+
         #[namespace = "commlib"]
         fn md5(data: &[u8]) -> String;
 
@@ -12,5 +15,74 @@ pub mod ffi_hash {
 
         #[namespace = "commlib"]
         fn md5_hash_bytes() -> usize;
+
+        #[namespace = "commlib"]
+        fn sha1(data: &[u8]) -> String;
+
+        #[namespace = "commlib"]
+        fn sha1_block_size() -> usize;
+
+        #[namespace = "commlib"]
+        fn sha1_hash_bytes() -> usize;
+
+        #[namespace = "commlib"]
+        fn sha256(data: &[u8]) -> String;
+
+        #[namespace = "commlib"]
+        fn sha256_block_size() -> usize;
+
+        #[namespace = "commlib"]
+        fn sha256_hash_bytes() -> usize;
+
+        #[namespace = "commlib"]
+        fn sha3(data: &[u8], bits: u32) -> String;
+
+        #[namespace = "commlib"]
+        fn keccak(data: &[u8], bits: u32) -> String;
+
+        #[namespace = "commlib"]
+        fn crc32(data: &[u8]) -> String;
+
+        #[namespace = "commlib"]
+        fn crc32_hash_bytes() -> usize;
+
+        #[namespace = "commlib"]
+        fn hmac_md5(data: &[u8], key: &[u8]) -> String;
+
+        #[namespace = "commlib"]
+        fn hmac_sha1(data: &[u8], key: &[u8]) -> String;
+
+        #[namespace = "commlib"]
+        fn hmac_sha256(data: &[u8], key: &[u8]) -> String;
+
+        #[namespace = "commlib"]
+        fn new_md5_hasher() -> SharedPtr<Hasher>;
+
+        #[namespace = "commlib"]
+        fn new_sha1_hasher() -> SharedPtr<Hasher>;
+
+        #[namespace = "commlib"]
+        fn new_sha256_hasher() -> SharedPtr<Hasher>;
+
+        #[namespace = "commlib"]
+        fn new_sha3_hasher(bits: u32) -> SharedPtr<Hasher>;
+
+        #[namespace = "commlib"]
+        fn new_keccak_hasher(bits: u32) -> SharedPtr<Hasher>;
+
+        #[namespace = "commlib"]
+        fn new_crc32_hasher() -> SharedPtr<Hasher>;
+
+        #[namespace = "commlib"]
+        fn hasher_update(h: SharedPtr<Hasher>, data: &[u8]);
+
+        #[namespace = "commlib"]
+        fn hasher_finalize_hex(h: SharedPtr<Hasher>) -> String;
+
+        #[namespace = "commlib"]
+        fn hasher_finalize_bytes(h: SharedPtr<Hasher>) -> Vec<u8>;
+
+        #[namespace = "commlib"]
+        fn hasher_reset(h: SharedPtr<Hasher>);
     }
 }
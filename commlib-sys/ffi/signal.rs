@@ -8,6 +8,24 @@ pub mod ffi_sig {
 
         #[namespace = "commlib"]
         fn init_signal_handlers(cb1: SignalCallback, cb2: SignalCallback, cb3: SignalCallback);
+
+        #[namespace = "commlib"]
+        fn install_signal_handler(signo: i32, cb: SignalCallback) -> Result<()>;
+
+        #[namespace = "commlib"]
+        fn ignore_signal(signo: i32) -> Result<()>;
+
+        #[namespace = "commlib"]
+        fn restore_default_signal(signo: i32) -> Result<()>;
+
+        #[namespace = "commlib"]
+        fn query_signal_disposition(signo: i32) -> Result<i32>;
+
+        #[namespace = "commlib"]
+        fn signal_rtmin() -> i32;
+
+        #[namespace = "commlib"]
+        fn signal_rtmax() -> i32;
     }
 }
 
@@ -18,3 +36,8 @@ unsafe impl cxx::ExternType for SignalCallback {
     type Id = cxx::type_id!("SignalCallback");
     type Kind = cxx::kind::Trivial;
 }
+
+/// Values returned by `query_signal_disposition`, see signal_bindings.h
+pub const SIGNAL_DISPOSITION_DEFAULT: i32 = 0;
+pub const SIGNAL_DISPOSITION_IGNORE: i32 = 1;
+pub const SIGNAL_DISPOSITION_HANDLER: i32 = 2;
@@ -6,14 +6,37 @@ include!("../ffi/signal.rs");
 
 pub mod crypto {
     pub use crate::ffi_crypto::{
-        blowfish_decrypt, blowfish_encrypt, blowfish_set_init_vec, blowfish_set_key, new_blowfish,
+        blowfish_decrypt, blowfish_decrypt_in_place, blowfish_encrypt, blowfish_encrypt_in_place,
+        blowfish_set_init_vec, blowfish_set_key, new_blowfish, BlowfishCfb64, BlowfishKeyLen,
     };
 }
 
 pub mod hash {
-    pub use crate::ffi_hash::md5;
+    pub use crate::ffi_hash::{crc32, keccak, md5, sha1, sha256, sha3};
+
+    pub use crate::ffi_hash::{hmac_md5, hmac_sha1, hmac_sha256};
+
+    pub use crate::ffi_hash::{
+        hasher_finalize_bytes, hasher_finalize_hex, hasher_reset, hasher_update, new_crc32_hasher,
+        new_keccak_hasher, new_md5_hasher, new_sha1_hasher, new_sha256_hasher, new_sha3_hasher,
+        Hasher,
+    };
 }
 
 pub mod sig {
     pub use crate::ffi_sig::init_signal_handlers;
+
+    pub use crate::ffi_sig::{
+        ignore_signal, install_signal_handler, query_signal_disposition, restore_default_signal,
+        signal_rtmax, signal_rtmin,
+    };
+    pub use crate::{
+        SIGNAL_DISPOSITION_DEFAULT, SIGNAL_DISPOSITION_HANDLER, SIGNAL_DISPOSITION_IGNORE,
+    };
+
+    pub use libc::{SIGABRT, SIGINT, SIGTERM};
+    #[cfg(unix)]
+    pub use libc::{SIGALRM, SIGCHLD, SIGHUP, SIGPIPE, SIGQUIT, SIGUSR1, SIGUSR2};
+    #[cfg(windows)]
+    pub const SIGBREAK: i32 = 21;
 }
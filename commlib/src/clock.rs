@@ -2,11 +2,16 @@
 //! Clock
 //!
 
-use std::cell::UnsafeCell;
-use std::ops::{AddAssign, SubAssign};
-use std::time::SystemTime;
+use std::cell::{RefCell, UnsafeCell};
+use std::collections::VecDeque;
+use std::time::Duration;
 
+use crate::hash_wheel_timer::wheel_timer::{CatchUpPolicy, UpdateBudget};
 use crate::hash_wheel_timer::{self, ClosureTimer, TimerReturn::Reschedule};
+use crate::{CronSchedule, Event, OffsetTimeSource, SystemTimeSource, TimeSource};
+
+/// 墙上时间偏离单调时间超过阈值（ms）时视为跳变
+pub const DEFAULT_CLOCK_JUMP_THRESHOLD: u64 = 1000;
 
 pub type WheelTimer = hash_wheel_timer::wheel_timer::WheelTimer<
     uuid::Uuid,
@@ -20,11 +25,124 @@ pub type WheelTimerEntry = hash_wheel_timer::wheel_timer::TimerEntry<
     hash_wheel_timer::PeriodicClosureState<uuid::Uuid>,
 >;
 
+/// 定时器句柄，用于取消定时器
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct TimerHandle(uuid::Uuid);
+
+impl TimerHandle {
+    /// 定时器 id
+    #[inline(always)]
+    pub fn id(&self) -> &uuid::Uuid {
+        &self.0
+    }
+
+    /// 转换成守卫，守卫销毁时自动取消定时器
+    #[inline(always)]
+    pub fn into_guard(self) -> TimerGuard {
+        TimerGuard::new(self)
+    }
+}
+
+/// 定时器守卫：drop 时自动取消定时器，随持有者（比如游戏对象）一起销毁
+///
+/// 定时器注册在当前线程的 [`G_CLOCK`] 上，所以守卫不能跨线程传递。
+#[must_use = "dropping the guard cancels the timer immediately"]
+pub struct TimerGuard {
+    handle: Option<TimerHandle>,
+    _not_send: std::marker::PhantomData<*const ()>,
+}
+
+impl TimerGuard {
+    /// 由定时器句柄构造守卫
+    pub fn new(handle: TimerHandle) -> Self {
+        Self {
+            handle: Some(handle),
+            _not_send: std::marker::PhantomData,
+        }
+    }
+
+    /// 守卫的定时器句柄
+    pub fn handle(&self) -> Option<&TimerHandle> {
+        self.handle.as_ref()
+    }
+
+    /// 解除守卫，定时器不再随守卫销毁而取消
+    pub fn disarm(mut self) -> TimerHandle {
+        self.handle.take().unwrap()
+    }
+}
+
+impl Drop for TimerGuard {
+    fn drop(&mut self) {
+        if let Some(handle) = self.handle.take() {
+            Clock::cancel_timer(handle);
+        }
+    }
+}
+
+/// 墙上时间跳变事件（调整系统时间，NTP 校时，GM 调时间），在 Clock 所在线程上触发
+///
+/// 时间轮由单调时间驱动，不受跳变影响；墙上时间定时器按新的时间重新计划。
+#[derive(Event)]
+#[event(namespace = "commlib")]
+pub struct ClockJumpEvent {
+    /// 按单调时间推算的 now stamp
+    pub expected: u64,
+    /// 跳变后的 now stamp
+    pub now_stamp: u64,
+}
+
+impl ClockJumpEvent {
+    /// 跳变量（ms），负数表示时间回拨
+    pub fn delta(&self) -> i64 {
+        self.now_stamp as i64 - self.expected as i64
+    }
+}
+
 thread_local! {
     /// tls 时钟
     pub static G_CLOCK: UnsafeCell<Clock> = {
         UnsafeCell::new(Clock::new())
     };
+
+    // 时间轮里到期的墙上时间定时器，时间轮 update 结束后在 G_CLOCK 外执行
+    static FIRED_WALL_JOBS: RefCell<VecDeque<uuid::Uuid>> = const { RefCell::new(VecDeque::new()) };
+}
+
+// 按墙上时间触发的定时器
+enum WallSchedule {
+    // 指定的 unix ms，只触发一次
+    At(u64),
+    // cron 表达式和时区偏移（秒）
+    Cron(CronSchedule, i32),
+}
+
+struct WallJob {
+    schedule: WallSchedule,
+
+    // 计划的下一次触发时间
+    next: u64,
+
+    // 最近一次触发的计划时间，时间回拨后不会重复触发
+    last_fire: Option<u64>,
+
+    // 触发时取出
+    f: Option<Box<dyn FnMut()>>,
+}
+
+impl WallJob {
+    fn next_fire(&self, now: u64) -> Option<u64> {
+        match &self.schedule {
+            WallSchedule::At(at) => match self.last_fire {
+                None => Some(*at),
+                Some(_) => None,
+            },
+            WallSchedule::Cron(cron, tz_offset) => {
+                let after = std::cmp::max(now, self.last_fire.unwrap_or(0));
+                cron.next_after(after, *tz_offset)
+            }
+        }
+    }
 }
 
 /// Clock utils
@@ -32,31 +150,54 @@ pub struct Clock {
     // 时间轮
     wheel_timer: WheelTimer,
 
-    // 用于计算 elapsed
-    last_time: SystemTime,
+    // 时间来源，偏移用于 GM 调时间
+    source: OffsetTimeSource<Box<dyn TimeSource>>,
+
+    // 上一次读取的单调时间，用于计算 elapsed
+    last_monotonic: u64,
+
+    // Clock::advance 快进的时间，叠加在单调时间和墙上时间上，与 GM 偏移分开
+    advanced: u64,
+
+    // 墙上时间跳变的阈值（ms）
+    jump_threshold: u64,
 
     // now stamp
     now_stamp: u64,
+
+    // 墙上时间定时器
+    wall_jobs: hashbrown::HashMap<uuid::Uuid, WallJob>,
 }
 
 impl Clock {
     /// New clock
     pub fn new() -> Self {
-        let now = SystemTime::now();
-        let now_stamp = now
-            .duration_since(SystemTime::UNIX_EPOCH)
-            .unwrap()
-            .as_millis() as u64;
+        Self::with_time_source(SystemTimeSource::new())
+    }
+
+    /// New clock reading time from `source`
+    pub fn with_time_source<S>(source: S) -> Self
+    where
+        S: TimeSource + 'static,
+    {
+        let source: OffsetTimeSource<Box<dyn TimeSource>> =
+            OffsetTimeSource::new(Box::new(source), 0);
+        let now_stamp = source.now_ms();
+        let last_monotonic = source.monotonic_ms();
 
         Self {
             wheel_timer: WheelTimer::new(),
-            last_time: now,
+            source,
+            last_monotonic,
+            advanced: 0,
+            jump_threshold: DEFAULT_CLOCK_JUMP_THRESHOLD,
             now_stamp,
+            wall_jobs: hashbrown::HashMap::new(),
         }
     }
 
     /// 循环定时器，立即执行一次
-    pub fn set_timer<F>(interval: u64, mut f: F)
+    pub fn set_timer<F>(interval: u64, mut f: F) -> TimerHandle
     where
         F: FnMut() + Send + Sync + 'static,
     {
@@ -67,15 +208,16 @@ impl Clock {
             let delay = std::time::Duration::from_millis(0);
             let period = std::time::Duration::from_millis(interval);
 
-            wheel_timer.schedule_action_periodic(id.clone(), delay, period, move |_timer_id| {
+            wheel_timer.schedule_action_periodic(id, delay, period, move |_timer_id| {
                 f();
                 Reschedule(())
             });
-        });
+            TimerHandle(id)
+        })
     }
 
     /// 循环定时器，延时一段时间之后开始执行
-    pub fn set_timer_delay<F>(delay: u64, interval: u64, mut f: F)
+    pub fn set_timer_delay<F>(delay: u64, interval: u64, mut f: F) -> TimerHandle
     where
         F: FnMut() + Send + Sync + 'static,
     {
@@ -90,11 +232,12 @@ impl Clock {
                 f();
                 Reschedule(())
             });
-        });
+            TimerHandle(id)
+        })
     }
 
     /// One-shot 一次性超时
-    pub fn set_timeout<F>(delay: u64, f: F)
+    pub fn set_timeout<F>(delay: u64, f: F) -> TimerHandle
     where
         F: FnOnce() + Send + Sync + 'static,
     {
@@ -107,43 +250,619 @@ impl Clock {
             wheel_timer.schedule_action_once(id, delay, move |_timer_id| {
                 f();
             });
-        });
+            TimerHandle(id)
+        })
+    }
+
+    /// 在指定的 unix 时间戳（ms）触发一次，时间已过则在下一次 update 时触发
+    pub fn set_at<F>(unix_ms: u64, f: F) -> TimerHandle
+    where
+        F: FnOnce() + 'static,
+    {
+        let mut f = Some(f);
+        with_tls_mut!(G_CLOCK, clock, {
+            clock.add_wall_job(WallSchedule::At(unix_ms), move || {
+                if let Some(f) = f.take() {
+                    f();
+                }
+            })
+        })
+    }
+
+    /// 每天 hh:mm:ss 触发，tz_offset 是时区偏移（秒，东八区为 8 * 3600）
+    pub fn set_daily<F>(
+        hh: u32,
+        mm: u32,
+        ss: u32,
+        tz_offset: i32,
+        f: F,
+    ) -> Result<TimerHandle, String>
+    where
+        F: FnMut() + 'static,
+    {
+        let cron = CronSchedule::daily(hh, mm, ss)?;
+        Ok(Self::set_schedule(cron, tz_offset, f))
+    }
+
+    /// 每周 weekday (0 = 周日, 1 = 周一 ... 6 = 周六) 的 hh:mm:ss 触发
+    pub fn set_weekly<F>(
+        weekday: u32,
+        hh: u32,
+        mm: u32,
+        ss: u32,
+        tz_offset: i32,
+        f: F,
+    ) -> Result<TimerHandle, String>
+    where
+        F: FnMut() + 'static,
+    {
+        let cron = CronSchedule::weekly(weekday, hh, mm, ss)?;
+        Ok(Self::set_schedule(cron, tz_offset, f))
+    }
+
+    /// 按 cron 表达式触发，见 [`CronSchedule`]
+    pub fn set_cron<F>(expr: &str, tz_offset: i32, f: F) -> Result<TimerHandle, String>
+    where
+        F: FnMut() + 'static,
+    {
+        let cron = CronSchedule::parse(expr)?;
+        Ok(Self::set_schedule(cron, tz_offset, f))
+    }
+
+    /// 按解析好的 cron 表达式触发
+    pub fn set_schedule<F>(cron: CronSchedule, tz_offset: i32, f: F) -> TimerHandle
+    where
+        F: FnMut() + 'static,
+    {
+        with_tls_mut!(G_CLOCK, clock, {
+            clock.add_wall_job(WallSchedule::Cron(cron, tz_offset), f)
+        })
+    }
+
+    /// 取消定时器，返回定时器是否仍然有效（未过期且未被取消）
+    ///
+    /// 可以在定时器自身的回调中取消，循环定时器不会再被调度。
+    pub fn cancel_timer(handle: TimerHandle) -> bool {
+        with_tls_mut!(G_CLOCK, clock, {
+            let wall = clock.wall_jobs.remove(handle.id()).is_some();
+            clock.wheel_timer.try_cancel(handle.id()).is_ok() || wall
+        })
+    }
+
+    /// 设置定时器错误的处理方式（比如配置了 0ms 的循环周期），默认记录日志并丢弃定时器
+    pub fn set_error_hook(hook: hash_wheel_timer::TimerErrorHook<uuid::Uuid>) {
+        with_tls_mut!(G_CLOCK, clock, {
+            clock.wheel_timer.set_error_hook(hook);
+        })
+    }
+
+    /// 限制每次 update 触发的定时器数量或耗时，超出的留到下一次 update，避免卡顿后一帧内集中触发
+    pub fn set_update_budget(budget: UpdateBudget) {
+        with_tls_mut!(G_CLOCK, clock, {
+            clock.wheel_timer.set_update_budget(budget);
+        })
+    }
+
+    /// 设置循环定时器错过触发时间（长时间没有 update）时的补偿方式，默认只补触发一次
+    pub fn set_catch_up(policy: CatchUpPolicy) {
+        with_tls_mut!(G_CLOCK, clock, {
+            clock.wheel_timer.set_catch_up(policy);
+        })
+    }
+
+    /// 因为 update 的限制而等待触发的定时器数量
+    pub fn timer_backlog() -> usize {
+        with_tls!(G_CLOCK, clock, { clock.wheel_timer.backlog() })
     }
 
     /// 更新计时器 tick
+    ///
+    /// 时间轮按单调时间推进；墙上时间跳变时触发 [`ClockJumpEvent`]。
     #[inline(always)]
     pub fn update() {
-        with_tls_mut!(G_CLOCK, clock, {
-            //
-            match clock.last_time.elapsed() {
-                Ok(d) => {
-                    // wheel timer update
-                    clock.wheel_timer.update(d);
+        let jump = with_tls_mut!(G_CLOCK, clock, { clock.sync() });
+        Self::finish_sync(jump);
+    }
+
+    /// 替换时间来源（偏移保留），now stamp 跳到新的时间，时间轮不动
+    pub fn set_time_source<S>(source: S)
+    where
+        S: TimeSource + 'static,
+    {
+        let jump = with_tls_mut!(G_CLOCK, clock, {
+            let offset = clock.source.offset();
+            clock.source = OffsetTimeSource::new(Box::new(source), offset);
+            clock.last_monotonic = clock.source.monotonic_ms() + clock.advanced;
+            clock.sync()
+        });
+        Self::finish_sync(jump);
+    }
 
-                    // advance last time
-                    clock.last_time.add_assign(d);
+    /// 时间快进 d，墙上时间和时间轮一起前进，立即驱动时间轮
+    pub fn advance(d: Duration) {
+        let jump = with_tls_mut!(G_CLOCK, clock, {
+            clock.advanced += d.as_millis() as u64;
+            clock.sync()
+        });
+        Self::finish_sync(jump);
+    }
 
-                    // advance now stamp
-                    clock.now_stamp += d.as_millis() as u64;
-                }
-                Err(err) => {
-                    //
-                    let d = err.duration();
-                    clock.last_time.sub_assign(d);
-
-                    //
-                    log::error!(
-                        "clock update error: {:?}!!! turn back last time to {:?}",
-                        err,
-                        clock.last_time
-                    );
-                }
-            }
+    /// 设置相对时间来源的偏移（ms），比如 GM 把时间调到 3 天后
+    ///
+    /// 只改变墙上时间，按跳变处理：时间轮不动，墙上时间定时器重新计划。
+    /// 偏移不包含 [`Clock::advance`] 快进的时间，设置偏移不会撤销快进。
+    pub fn set_offset(offset: i64) {
+        let jump = with_tls_mut!(G_CLOCK, clock, {
+            clock.source.set_offset(offset);
+            clock.sync()
         });
+        Self::finish_sync(jump);
+    }
+
+    /// 设置墙上时间跳变的阈值，默认 [`DEFAULT_CLOCK_JUMP_THRESHOLD`]
+    pub fn set_jump_threshold(threshold: Duration) {
+        with_tls_mut!(G_CLOCK, clock, {
+            clock.jump_threshold = threshold.as_millis() as u64;
+        });
+    }
+
+    /// 相对时间来源的偏移（ms），不包含 [`Clock::advance`] 快进的时间
+    pub fn offset() -> i64 {
+        with_tls!(G_CLOCK, clock, { clock.source.offset() })
     }
 
     ///
     pub fn now_stamp() -> u64 {
         with_tls_mut!(G_CLOCK, clock, { clock.now_stamp })
     }
+
+    // 在 G_CLOCK 外执行到期的墙上时间定时器、触发跳变事件，回调里可以调用 Clock 的接口
+    fn finish_sync(jump: Option<ClockJumpEvent>) {
+        Self::run_wall_jobs();
+        if let Some(event) = jump {
+            event.trigger();
+        }
+    }
+
+    // 读取时间来源：单调时间驱动时间轮，墙上时间偏离超过阈值视为跳变
+    fn sync(&mut self) -> Option<ClockJumpEvent> {
+        let monotonic = self.source.monotonic_ms() + self.advanced;
+        let elapsed = monotonic.saturating_sub(self.last_monotonic);
+        self.last_monotonic = std::cmp::max(monotonic, self.last_monotonic);
+
+        let now = self.source.now_ms() + self.advanced;
+        let expected = self.now_stamp + elapsed;
+        let jump = if now.abs_diff(expected) > self.jump_threshold {
+            log::warn!(
+                "clock jumped {}ms: expected now stamp {}, got {}",
+                now as i64 - expected as i64,
+                expected,
+                now
+            );
+            self.jump_to(now);
+            Some(ClockJumpEvent {
+                expected,
+                now_stamp: now,
+            })
+        } else {
+            // 小的偏差（毫秒取整，慢速校时）直接跟随墙上时间
+            self.now_stamp = now;
+            None
+        };
+
+        self.advance_by(Duration::from_millis(elapsed));
+        jump
+    }
+
+    fn advance_by(&mut self, d: Duration) {
+        // 时间轮一次最多推进 u32::MAX ms，时间不变时也要处理上一次留下的定时器
+        let mut ms = d.as_millis();
+        loop {
+            let step = std::cmp::min(ms, u32::MAX as u128);
+            self.wheel_timer.update(Duration::from_millis(step as u64));
+            ms -= step;
+            if ms == 0 {
+                break;
+            }
+        }
+    }
+
+    // 墙上时间跳变，时间轮不动，墙上时间定时器按原计划的时间重新放进时间轮：
+    // 往后跳过了计划时间的触发一次，往回跳的不会重复触发
+    fn jump_to(&mut self, now: u64) {
+        self.now_stamp = now;
+        let jobs: Vec<(uuid::Uuid, u64)> = self
+            .wall_jobs
+            .iter()
+            .map(|(id, job)| (*id, job.next))
+            .collect();
+        for (id, next) in jobs {
+            self.schedule_wall_job(id, next);
+        }
+    }
+
+    fn add_wall_job<F>(&mut self, schedule: WallSchedule, f: F) -> TimerHandle
+    where
+        F: FnMut() + 'static,
+    {
+        let id = uuid::Uuid::new_v4();
+        self.wall_jobs.insert(
+            id,
+            WallJob {
+                schedule,
+                next: 0,
+                last_fire: None,
+                f: Some(Box::new(f)),
+            },
+        );
+        self.plan_wall_job(id);
+        TimerHandle(id)
+    }
+
+    // 计算下一次触发时间并放进时间轮，不再触发的移除
+    fn plan_wall_job(&mut self, id: uuid::Uuid) {
+        let now = self.now_stamp;
+        let Some(job) = self.wall_jobs.get_mut(&id) else {
+            return;
+        };
+        match job.next_fire(now) {
+            Some(next) => {
+                job.next = next;
+                self.schedule_wall_job(id, next);
+            }
+            None => {
+                self.wall_jobs.remove(&id);
+                let _ = self.wheel_timer.try_cancel(&id);
+            }
+        }
+    }
+
+    // 按当前墙上时间把 next 换算成时间轮的延时
+    fn schedule_wall_job(&mut self, id: uuid::Uuid, next: u64) {
+        let delay = Duration::from_millis(std::cmp::max(next.saturating_sub(self.now_stamp), 1));
+        if self.wheel_timer.reschedule(&id, delay).is_err() {
+            self.wheel_timer.schedule_action_once(id, delay, |id| {
+                // 时间轮 update 中不能访问 G_CLOCK，只记下 id
+                FIRED_WALL_JOBS.with(|jobs| jobs.borrow_mut().push_back(id));
+            });
+        }
+    }
+
+    fn run_wall_jobs() {
+        while let Some(id) = FIRED_WALL_JOBS.with(|jobs| jobs.borrow_mut().pop_front()) {
+            Self::fire_wall_job(id);
+        }
+    }
+
+    fn fire_wall_job(id: uuid::Uuid) {
+        // 回调里可以调用 Clock 的接口，调用期间不持有 job
+        let f = with_tls_mut!(G_CLOCK, clock, {
+            clock.wall_jobs.get_mut(&id).and_then(|job| {
+                job.last_fire = Some(job.next);
+                job.f.take()
+            })
+        });
+        let Some(mut f) = f else {
+            return;
+        };
+        f();
+
+        with_tls_mut!(G_CLOCK, clock, {
+            // 回调里取消了的不再调度
+            if let Some(job) = clock.wall_jobs.get_mut(&id) {
+                job.f = Some(f);
+                clock.plan_wall_job(id);
+            }
+        });
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::atomic::{AtomicUsize, Ordering};
+    use std::sync::Arc;
+    use std::thread::sleep;
+    use std::time::Duration;
+
+    use super::{Clock, ClockJumpEvent, G_CLOCK};
+    use crate::hash_wheel_timer::wheel_timer::UpdateBudget;
+    use crate::{Event, ManualTimeSource, TimeSource};
+
+    fn run_for(ms: u64) {
+        for _ in 0..ms {
+            sleep(Duration::from_millis(1));
+            Clock::update();
+        }
+    }
+
+    #[test]
+    fn test_cancel_timer() {
+        let counter = Arc::new(AtomicUsize::new(0));
+        let c = counter.clone();
+        let handle = Clock::set_timer_delay(1, 1, move || {
+            c.fetch_add(1, Ordering::SeqCst);
+        });
+
+        run_for(10);
+        assert!(counter.load(Ordering::SeqCst) > 0);
+
+        assert!(Clock::cancel_timer(handle));
+        assert!(!Clock::cancel_timer(handle));
+
+        let fired = counter.load(Ordering::SeqCst);
+        run_for(10);
+        assert_eq!(fired, counter.load(Ordering::SeqCst));
+    }
+
+    #[test]
+    fn test_cancel_timer_from_own_action() {
+        let counter = Arc::new(AtomicUsize::new(0));
+        let handle = Arc::new(parking_lot::Mutex::new(None));
+
+        let c = counter.clone();
+        let h = handle.clone();
+        *handle.lock() = Some(Clock::set_timer_delay(1, 1, move || {
+            c.fetch_add(1, Ordering::SeqCst);
+            if let Some(handle) = *h.lock() {
+                Clock::cancel_timer(handle);
+            }
+        }));
+
+        run_for(10);
+        assert_eq!(1, counter.load(Ordering::SeqCst));
+    }
+
+    #[test]
+    fn test_timer_guard() {
+        let counter = Arc::new(AtomicUsize::new(0));
+        let c = counter.clone();
+        let guard = Clock::set_timeout(5, move || {
+            c.fetch_add(1, Ordering::SeqCst);
+        })
+        .into_guard();
+
+        drop(guard);
+        run_for(10);
+        assert_eq!(0, counter.load(Ordering::SeqCst));
+
+        let c = counter.clone();
+        let handle = Clock::set_timeout(5, move || {
+            c.fetch_add(1, Ordering::SeqCst);
+        })
+        .into_guard()
+        .disarm();
+
+        run_for(10);
+        assert_eq!(1, counter.load(Ordering::SeqCst));
+        assert!(!Clock::cancel_timer(handle));
+    }
+
+    #[test]
+    fn test_set_at() {
+        let counter = Arc::new(AtomicUsize::new(0));
+        let now = Clock::now_stamp();
+
+        let c = counter.clone();
+        Clock::set_at(now + 5, move || {
+            c.fetch_add(1, Ordering::SeqCst);
+        });
+        // already passed
+        let c = counter.clone();
+        Clock::set_at(now - 1000, move || {
+            c.fetch_add(10, Ordering::SeqCst);
+        });
+        let c = counter.clone();
+        let handle = Clock::set_at(now + 5, move || {
+            c.fetch_add(100, Ordering::SeqCst);
+        });
+        assert!(Clock::cancel_timer(handle));
+
+        run_for(20);
+        assert_eq!(11, counter.load(Ordering::SeqCst));
+        with_tls!(G_CLOCK, clock, {
+            assert!(clock.wall_jobs.is_empty());
+        });
+    }
+
+    #[test]
+    fn test_cron_time_jump() {
+        assert!(Clock::set_cron("* * *", 0, || {}).is_err());
+        assert!(Clock::set_daily(5, 60, 0, 8 * 3600, || {}).is_err());
+
+        let source = ManualTimeSource::new(1_700_000_000_123);
+        Clock::set_time_source(source.clone());
+
+        let counter = Arc::new(AtomicUsize::new(0));
+        let c = counter.clone();
+        let handle = Clock::set_cron("*/10 * * * * *", 0, move || {
+            c.fetch_add(1, Ordering::SeqCst);
+        })
+        .unwrap();
+
+        let advance = |secs: u64| {
+            for _ in 0..secs {
+                Clock::advance(Duration::from_secs(1));
+            }
+        };
+
+        // any 60s window holds six occurrences
+        advance(60);
+        assert_eq!(6, counter.load(Ordering::SeqCst));
+
+        // time goes back 20s, the same occurrences don't fire again
+        source.set(source.now_ms() - 20_000);
+        Clock::update();
+        assert_eq!(Clock::now_stamp(), 1_700_000_040_123);
+        advance(20);
+        assert_eq!(6, counter.load(Ordering::SeqCst));
+        advance(10);
+        assert_eq!(7, counter.load(Ordering::SeqCst));
+
+        // a jump forward fires once, missed occurrences are skipped
+        Clock::advance(Duration::from_secs(35));
+        assert_eq!(8, counter.load(Ordering::SeqCst));
+
+        assert!(Clock::cancel_timer(handle));
+        advance(20);
+        assert_eq!(8, counter.load(Ordering::SeqCst));
+    }
+
+    #[test]
+    fn test_manual_time_source() {
+        let jumps = Arc::new(parking_lot::Mutex::new(Vec::new()));
+        let j = jumps.clone();
+        let _subscription = ClockJumpEvent::add_callback(move |e| j.lock().push(e.delta()));
+
+        let source = ManualTimeSource::new(1_700_000_000_000);
+        Clock::set_time_source(source.clone());
+        assert_eq!(Clock::now_stamp(), 1_700_000_000_000);
+        assert_eq!(1, jumps.lock().len());
+        jumps.lock().clear();
+
+        let once = Arc::new(AtomicUsize::new(0));
+        let periodic = Arc::new(AtomicUsize::new(0));
+        let daily = Arc::new(AtomicUsize::new(0));
+
+        let c = once.clone();
+        Clock::set_timeout(100, move || {
+            c.fetch_add(1, Ordering::SeqCst);
+        });
+        let c = periodic.clone();
+        let handle = Clock::set_timer_delay(10, 10, move || {
+            c.fetch_add(1, Ordering::SeqCst);
+        });
+        let c = daily.clone();
+        Clock::set_daily(5, 0, 0, 8 * 3600, move || {
+            c.fetch_add(1, Ordering::SeqCst);
+        })
+        .unwrap();
+
+        // time only moves with the source
+        sleep(Duration::from_millis(5));
+        Clock::update();
+        assert_eq!(0, periodic.load(Ordering::SeqCst));
+
+        for _ in 0..5 {
+            source.advance(Duration::from_millis(10));
+            Clock::update();
+        }
+        assert_eq!(5, periodic.load(Ordering::SeqCst));
+
+        for _ in 0..5 {
+            Clock::advance(Duration::from_millis(10));
+        }
+        assert_eq!(1, once.load(Ordering::SeqCst));
+        assert_eq!(10, periodic.load(Ordering::SeqCst));
+        assert_eq!(Clock::now_stamp(), 1_700_000_000_100);
+        assert_eq!(Clock::offset(), 0);
+        Clock::cancel_timer(handle);
+
+        // Clock::advance is not a jump
+        assert!(jumps.lock().is_empty());
+
+        // GM: 3 days later, the daily job fires once on the next tick
+        let three_days = 3 * 86400 * 1000;
+        Clock::set_offset(three_days);
+        assert_eq!(Clock::now_stamp(), 1_700_000_000_100 + three_days as u64);
+        Clock::advance(Duration::from_millis(1));
+        assert_eq!(1, daily.load(Ordering::SeqCst));
+
+        // and back, nothing fires again
+        Clock::set_offset(0);
+        assert_eq!(Clock::now_stamp(), 1_700_000_000_101);
+        source.advance(Duration::from_millis(1000));
+        Clock::update();
+        assert_eq!(1, daily.load(Ordering::SeqCst));
+        assert_eq!(1, once.load(Ordering::SeqCst));
+        assert_eq!(*jumps.lock(), vec![three_days, -three_days]);
+    }
+
+    #[test]
+    fn test_advance_then_set_offset() {
+        let jumps = Arc::new(parking_lot::Mutex::new(Vec::new()));
+        let j = jumps.clone();
+        let _subscription = ClockJumpEvent::add_callback(move |e| j.lock().push(e.delta()));
+
+        let source = ManualTimeSource::new(1_700_000_000_000);
+        Clock::set_time_source(source.clone());
+        jumps.lock().clear();
+
+        let hour = 3600 * 1000;
+        Clock::advance(Duration::from_millis(hour));
+        assert_eq!(Clock::now_stamp(), 1_700_000_000_000 + hour);
+
+        // resetting the GM offset doesn't undo the advance
+        Clock::set_offset(0);
+        assert_eq!(Clock::now_stamp(), 1_700_000_000_000 + hour);
+        assert!(jumps.lock().is_empty());
+
+        Clock::set_offset(60_000);
+        assert_eq!(Clock::now_stamp(), 1_700_000_060_000 + hour);
+        Clock::set_offset(0);
+        assert_eq!(Clock::now_stamp(), 1_700_000_000_000 + hour);
+        assert_eq!(*jumps.lock(), vec![60_000, -60_000]);
+    }
+
+    #[test]
+    fn test_update_budget() {
+        let source = ManualTimeSource::new(1_700_000_000_000);
+        Clock::set_time_source(source.clone());
+        Clock::set_update_budget(UpdateBudget::callbacks(4));
+
+        let counter = Arc::new(AtomicUsize::new(0));
+        for _ in 0..10 {
+            let c = counter.clone();
+            Clock::set_timeout(10, move || {
+                c.fetch_add(1, Ordering::SeqCst);
+            });
+        }
+
+        Clock::advance(Duration::from_secs(1));
+        assert_eq!(4, counter.load(Ordering::SeqCst));
+        assert_eq!(6, Clock::timer_backlog());
+
+        // carried over to the next updates, even if no time passed
+        Clock::update();
+        Clock::update();
+        assert_eq!(10, counter.load(Ordering::SeqCst));
+        assert_eq!(0, Clock::timer_backlog());
+        Clock::set_update_budget(UpdateBudget::UNLIMITED);
+    }
+
+    #[test]
+    fn test_wall_clock_jump_keeps_timers() {
+        let jumps = Arc::new(parking_lot::Mutex::new(Vec::new()));
+        let j = jumps.clone();
+        let _subscription = ClockJumpEvent::add_callback(move |e| j.lock().push(e.delta()));
+
+        let source = ManualTimeSource::new(1_700_000_000_000);
+        Clock::set_time_source(source.clone());
+        jumps.lock().clear();
+
+        let fired = Arc::new(AtomicUsize::new(0));
+        let c = fired.clone();
+        Clock::set_timer_delay(100, 100, move || {
+            c.fetch_add(1, Ordering::SeqCst);
+        });
+
+        // system time set back an hour: relative timers keep running
+        source.set(source.now_ms() - 3_600_000);
+        for _ in 0..10 {
+            source.advance(Duration::from_millis(50));
+            Clock::update();
+        }
+        assert_eq!(5, fired.load(Ordering::SeqCst));
+        assert_eq!(Clock::now_stamp(), 1_700_000_000_500 - 3_600_000);
+
+        // a large step forward doesn't fire a burst
+        source.set(source.now_ms() + 86_400_000);
+        source.advance(Duration::from_millis(100));
+        Clock::update();
+        assert_eq!(6, fired.load(Ordering::SeqCst));
+
+        // small drift is followed without a jump
+        source.set(source.now_ms() + 500);
+        Clock::update();
+        assert_eq!(*jumps.lock(), vec![-3_600_000, 86_400_000]);
+    }
 }
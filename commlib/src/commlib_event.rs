@@ -4,20 +4,245 @@
 //! EventDispatcher use "observer pattern"
 //! Observer is a behavioral design pattern that allows one objects to notify other objects about changes in their state.
 
-use crate::StopWatch;
+use std::cell::RefCell;
+use std::panic::Location;
+use std::thread::LocalKey;
+use std::time::Duration;
+
+use crate::commlib_event_stats::CallSamples;
+use crate::{EventQueue, EventStats, HandlerStats, StopWatch, DEFAULT_EVENT_SLOW_THRESHOLD};
+
+/// 事件分发日志的 target
+pub const EVENT_LOG_TARGET: &str = "commlib::event";
+
+/// 事件分发日志的默认级别
+pub const DEFAULT_EVENT_LOG_LEVEL: log::LevelFilter = log::LevelFilter::Trace;
 
 /// Trait to signal that this is an event type.
-pub trait Event {
+///
+/// 推荐使用 `#[derive(Event)]`：
+///
+/// ```rust
+/// use commlib::Event;
+///
+/// #[derive(Event)]
+/// #[event(namespace = "login", id = 1001)]
+/// struct PlayerLogin {
+///     uid: u64,
+/// }
+///
+/// PlayerLogin::add_callback(|e| assert_eq!(e.uid, 1));
+/// PlayerLogin { uid: 1 }.trigger();
+/// assert_eq!(PlayerLogin::EVENT_NAME, "login_PlayerLogin");
+/// assert_eq!(PlayerLogin::EVENT_ID, 1001);
+/// ```
+///
+/// 重复 id 的检查需要手动开启：derive 不会检查其他类型的 id，用 [`assert_unique_event_ids!`]
+/// 列出事件类型后，同一命名空间内重复的数字 id 编译失败，没有列出的类型不检查：
+///
+/// ```rust, compile_fail
+/// use commlib::Event;
+///
+/// #[derive(Event)]
+/// #[event(namespace = "login", id = 1001)]
+/// struct PlayerLogin;
+///
+/// #[derive(Event)]
+/// #[event(namespace = "login", id = 1001)]
+/// struct PlayerLogout;
+///
+/// commlib::assert_unique_event_ids!(PlayerLogin, PlayerLogout);
+/// ```
+///
+/// 泛型类型不能派生：
+///
+/// ```rust, compile_fail
+/// use commlib::Event;
+///
+/// #[derive(Event)]
+/// #[event(namespace = "login")]
+/// struct PlayerLogin<T>(T);
+/// ```
+pub trait Event: Sized + 'static {
+    /// 命名空间
+    const EVENT_NAMESPACE: &'static str;
+
+    /// 字符串 id："命名空间_类型名"
+    const EVENT_NAME: &'static str;
+
+    /// 数字 id，同一命名空间内唯一
+    const EVENT_ID: u32;
+
     /// Id string
-    fn id(&self) -> &str;
+    fn id(&self) -> &str {
+        Self::EVENT_NAME
+    }
+
+    /// 当前线程的监听者
+    fn listener() -> &'static LocalKey<RefCell<EventListener<Self>>>;
 
     /// Add callback for event
-    fn add_callback<'a, F>(f: F)
+    #[track_caller]
+    fn add_callback<F>(f: F) -> EventSubscription
     where
-        F: FnMut(&Self) + 'static;
+        F: FnMut(&Self) + 'static,
+    {
+        Self::add_handler(EventHandler::<Self>::new(f))
+    }
+
+    /// Add handler (with priority, once, propagation control) for event
+    fn add_handler(h: EventHandler<Self>) -> EventSubscription {
+        let id = Self::with_listener(|l| l.listen_event(h));
+        EventSubscription::new(id, Self::remove_callback)
+    }
+
+    /// Remove handler by subscription id
+    fn remove_callback(id: u64) -> bool {
+        Self::with_listener(|l| l.remove(id))
+    }
 
     /// Trigger event callback
-    fn trigger<'a>(&mut self);
+    ///
+    /// 在处理函数里再次触发同一事件时不会递归，事件进入 [`EventQueue`] 等待 flush。
+    fn trigger(self) {
+        dispatch_event(Self::listener(), self);
+    }
+
+    /// 事件进入当前线程的 [`EventQueue`]，在 flush 时触发
+    fn post(self) {
+        EventQueue::push(move || self.trigger());
+    }
+
+    /// Access the listener of current thread
+    fn with_listener<R, F>(f: F) -> R
+    where
+        F: FnOnce(&mut EventListener<Self>) -> R,
+    {
+        Self::listener().with(|g| f(&mut g.borrow_mut()))
+    }
+
+    /// 设置慢事件阈值，单次触发超过阈值时打印日志
+    fn set_slow_threshold(threshold: Duration) {
+        Self::with_listener(|l| l.set_slow_threshold(threshold));
+    }
+
+    /// 设置分发日志的级别，LevelFilter::Off 关闭
+    fn set_log_level(level: log::LevelFilter) {
+        Self::with_listener(|l| l.set_log_level(level));
+    }
+
+    /// 当前线程的调用统计，没有触发过时返回 None
+    fn stats() -> Option<EventStats> {
+        Self::with_listener(|l| l.stats())
+    }
+}
+
+/// 由字符串 id 得到数字 id（FNV-1a 32），与 `#[derive(Event)]` 一致
+pub const fn event_id_hash(name: &str) -> u32 {
+    let bytes = name.as_bytes();
+    let mut hash: u32 = 0x811c_9dc5;
+    let mut i = 0;
+    while i < bytes.len() {
+        hash ^= bytes[i] as u32;
+        hash = hash.wrapping_mul(0x0100_0193);
+        i += 1;
+    }
+    hash
+}
+
+/// 两个事件的 id 是否冲突：同一命名空间内数字 id 相同，或字符串 id 相同
+pub const fn event_ids_collide<A: Event, B: Event>() -> bool {
+    (str_eq(A::EVENT_NAMESPACE, B::EVENT_NAMESPACE) && A::EVENT_ID == B::EVENT_ID)
+        || str_eq(A::EVENT_NAME, B::EVENT_NAME)
+}
+
+const fn str_eq(a: &str, b: &str) -> bool {
+    let (a, b) = (a.as_bytes(), b.as_bytes());
+    if a.len() != b.len() {
+        return false;
+    }
+    let mut i = 0;
+    while i < a.len() {
+        if a[i] != b[i] {
+            return false;
+        }
+        i += 1;
+    }
+    true
+}
+
+/// 编译时检查事件 id 不重复，`#[derive(Event)]` 和 `impl_event_for!` 的事件都可以列出
+///
+/// 只检查列出的类型，没有列出的类型即使 id 重复也能编译。
+///
+/// ```rust
+/// use commlib::Event;
+///
+/// #[derive(Event)]
+/// #[event(namespace = "login", id = 1001)]
+/// struct PlayerLogin;
+///
+/// #[derive(Event)]
+/// #[event(namespace = "login", id = 1002)]
+/// struct PlayerLogout;
+///
+/// commlib::assert_unique_event_ids!(PlayerLogin, PlayerLogout);
+/// ```
+#[macro_export]
+macro_rules! assert_unique_event_ids {
+    ($($t:ty),+ $(,)?) => {
+        const _: () = {
+            $crate::assert_unique_event_ids!(@check $($t),+);
+        };
+    };
+    (@check $first:ty) => {};
+    (@check $first:ty, $($rest:ty),+) => {
+        $(
+            assert!(
+                !$crate::event_ids_collide::<$first, $rest>(),
+                concat!("duplicate event id: ", stringify!($first), " and ", stringify!($rest))
+            );
+        )+
+        $crate::assert_unique_event_ids!(@check $($rest),+);
+    };
+}
+
+/// 事件处理函数的返回值：是否继续传递给后续的处理函数
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Propagation {
+    Continue,
+    Stop,
+}
+
+/// 订阅凭证，用于移除处理函数
+///
+/// 监听者是 thread_local 的，所以凭证只能在注册的线程上使用。丢弃凭证不会移除处理函数。
+#[derive(Debug)]
+pub struct EventSubscription {
+    id: u64,
+    remover: fn(u64) -> bool,
+    _not_send: std::marker::PhantomData<*const ()>,
+}
+
+impl EventSubscription {
+    /// 由订阅 id 和对应事件类型的移除函数构造
+    pub fn new(id: u64, remover: fn(u64) -> bool) -> Self {
+        Self {
+            id,
+            remover,
+            _not_send: std::marker::PhantomData,
+        }
+    }
+
+    /// 订阅 id
+    pub fn id(&self) -> u64 {
+        self.id
+    }
+
+    /// 移除处理函数，已经移除（比如 once 已触发）时返回 false
+    pub fn remove(self) -> bool {
+        (self.remover)(self.id)
+    }
 }
 
 /// Handler of event
@@ -25,7 +250,13 @@ pub struct EventHandler<E>
 where
     E: Event,
 {
-    pub func: Box<dyn FnMut(&E)>,
+    pub func: Box<dyn FnMut(&E) -> Propagation>,
+    priority: i32,
+    once: bool,
+    location: &'static Location<'static>,
+    count: u64,
+    total: Duration,
+    max: Duration,
     _phantom: std::marker::PhantomData<E>,
 }
 
@@ -35,22 +266,73 @@ where
     E: Event,
 {
     // Construct
-    pub fn new<F>(f: F) -> Self
+    #[track_caller]
+    pub fn new<F>(mut f: F) -> Self
     where
         F: FnMut(&E) + 'static,
+    {
+        Self::with_propagation(move |e| {
+            f(e);
+            Propagation::Continue
+        })
+    }
+
+    /// Construct with handler deciding whether the event goes on to later handlers
+    #[track_caller]
+    pub fn with_propagation<F>(f: F) -> Self
+    where
+        F: FnMut(&E) -> Propagation + 'static,
     {
         Self {
             func: Box::new(f),
+            priority: 0,
+            once: false,
+            location: Location::caller(),
+            count: 0,
+            total: Duration::ZERO,
+            max: Duration::ZERO,
             _phantom: std::marker::PhantomData,
         }
     }
 
+    /// 优先级高的先调用，相同优先级按注册顺序调用，默认为 0
+    pub fn priority(mut self, priority: i32) -> Self {
+        self.priority = priority;
+        self
+    }
+
+    /// 只触发一次，触发后自动移除
+    pub fn once(mut self) -> Self {
+        self.once = true;
+        self
+    }
+
+    /// 注册位置
+    pub fn location(&self) -> &'static Location<'static> {
+        self.location
+    }
+
+    /// 调用统计
+    pub fn stats(&self, id: u64) -> HandlerStats {
+        HandlerStats {
+            id,
+            location: self.location,
+            count: self.count,
+            total: self.total,
+            max: self.max,
+        }
+    }
+
+    fn record(&mut self, cost: Duration) {
+        self.count += 1;
+        self.total += cost;
+        self.max = std::cmp::max(self.max, cost);
+    }
+
     /// Call
-    pub fn handle(&mut self, e: &E) {
-        let repeat = "*".repeat(20);
-        println!("{} Begin {}", repeat, repeat);
-        (self.func)(e);
-        println!("{} End   {}", repeat, repeat);
+    #[inline(always)]
+    pub fn handle(&mut self, e: &E) -> Propagation {
+        (self.func)(e)
     }
 }
 
@@ -59,7 +341,25 @@ pub struct EventListener<E>
 where
     E: Event,
 {
-    handlers: Vec<EventHandler<E>>,
+    handlers: Vec<(u64, EventHandler<E>)>,
+    next_id: u64,
+    slow_threshold: Duration,
+    log_level: log::LevelFilter,
+    event_id: Option<String>,
+    samples: CallSamples,
+    slowest: Option<HandlerStats>,
+
+    // 分发中时处理函数被取出，记录分发中移除的处理函数
+    dispatching: Option<Vec<u64>>,
+    removed_in_dispatch: Vec<u64>,
+}
+
+/// 一次分发的结果
+struct CallReport {
+    cost: Duration,
+    handled: usize,
+    hotspot: Option<(&'static Location<'static>, Duration)>,
+    slowest: Option<HandlerStats>,
 }
 
 /// Impl EventListener
@@ -67,59 +367,772 @@ impl<E> EventListener<E>
 where
     E: Event,
 {
+    /// 线程局部监听者，登记到 [`crate::dump_event_stats`]
+    #[doc(hidden)]
+    pub fn new_registered() -> Self {
+        crate::register_event_stats(<E as Event>::stats);
+        Self::new()
+    }
+
     pub fn new() -> Self {
         Self {
             handlers: Vec::new(),
+            next_id: 1,
+            slow_threshold: DEFAULT_EVENT_SLOW_THRESHOLD,
+            log_level: DEFAULT_EVENT_LOG_LEVEL,
+            event_id: None,
+            samples: CallSamples::default(),
+            slowest: None,
+            dispatching: None,
+            removed_in_dispatch: Vec::new(),
         }
     }
 
-    pub fn listen_event(&mut self, h: EventHandler<E>) {
-        self.handlers.push(h);
+    /// 按优先级插入处理函数，返回订阅 id
+    pub fn listen_event(&mut self, h: EventHandler<E>) -> u64 {
+        let id = self.next_id;
+        self.next_id += 1;
+        self.insert(id, h);
+        id
+    }
+
+    fn insert(&mut self, id: u64, h: EventHandler<E>) {
+        let pos = self
+            .handlers
+            .iter()
+            .position(|(_, other)| other.priority < h.priority)
+            .unwrap_or(self.handlers.len());
+        self.handlers.insert(pos, (id, h));
     }
 
-    pub fn call(&mut self, e: &E) {
-        let sw = StopWatch::new();
-        for h in &mut self.handlers {
-            h.handle(e);
+    /// 移除处理函数
+    pub fn remove(&mut self, id: u64) -> bool {
+        if let Some(pos) = self.handlers.iter().position(|(hid, _)| *hid == id) {
+            self.handlers.remove(pos);
+            return true;
         }
 
-        let cost = sw.elapsed();
-        if cost > 10_u128 {
-            log::error!(
-                "call on event ID={} timeout cost: {}ms, hotspot **@{}:{}",
+        // 分发中：处理函数已被取出，先记下，分发结束时移除
+        match &self.dispatching {
+            Some(ids) if ids.contains(&id) && !self.removed_in_dispatch.contains(&id) => {
+                self.removed_in_dispatch.push(id);
+                true
+            }
+            _ => false,
+        }
+    }
+
+    /// 是否正在分发（在处理函数中再次触发同一事件）
+    pub fn is_dispatching(&self) -> bool {
+        self.dispatching.is_some()
+    }
+
+    /// 处理函数数量
+    pub fn len(&self) -> usize {
+        let dispatching = self.dispatching.as_ref().map_or(0, |ids| ids.len());
+        self.handlers.len() + dispatching - self.removed_in_dispatch.len()
+    }
+
+    /// 是否没有处理函数
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// 慢事件阈值，默认 10ms
+    pub fn set_slow_threshold(&mut self, threshold: Duration) {
+        self.slow_threshold = threshold;
+    }
+
+    /// 分发日志的级别，默认 Trace
+    pub fn set_log_level(&mut self, level: log::LevelFilter) {
+        self.log_level = level;
+    }
+
+    /// 调用统计，没有触发过时返回 None
+    pub fn stats(&self) -> Option<EventStats> {
+        let event_id = self.event_id.clone()?;
+        let (p50, p99) = self.samples.percentiles();
+        Some(EventStats {
+            event_id,
+            count: self.samples.count(),
+            p50,
+            p99,
+            max: self.samples.max(),
+            slowest_handler: self.slowest.clone(),
+            handlers: self.handlers.iter().map(|(id, h)| h.stats(*id)).collect(),
+        })
+    }
+
+    /// 调用处理函数
+    ///
+    /// 只有线程局部监听者能在分发中被再次调用（在处理函数里通过 [`Event::with_listener`]），
+    /// 这时事件进入 [`EventQueue`]，等待下次 flush。
+    pub fn call(&mut self, e: E) {
+        let Some(handlers) = self.begin_call(&e) else {
+            log::debug!(
+                target: EVENT_LOG_TARGET,
+                "event ID={} called while dispatching, deferred to event queue",
+                e.id()
+            );
+            EventQueue::push(move || dispatch_event(E::listener(), e));
+            return;
+        };
+
+        let log_level = self.enabled_log_level();
+        let mut guard = DispatchGuard::new(handlers, |handlers| self.restore_handlers(handlers));
+        let report = run_handlers(&mut guard.handlers, &e, log_level, |_| false, |_| {});
+        let handlers = guard.finish();
+        self.end_call(handlers, &e, report);
+    }
+
+    #[inline(always)]
+    fn enabled_log_level(&self) -> Option<log::Level> {
+        self.log_level
+            .to_level()
+            .filter(|level| log::log_enabled!(target: EVENT_LOG_TARGET, *level))
+    }
+
+    #[inline(always)]
+    fn is_removed_in_dispatch(&self, id: u64) -> bool {
+        self.removed_in_dispatch.contains(&id)
+    }
+
+    /// once 处理函数在分发中触发后被移除
+    #[inline(always)]
+    fn detach_in_dispatch(&mut self, id: u64) {
+        if let Some(ids) = &mut self.dispatching {
+            ids.retain(|hid| *hid != id);
+        }
+    }
+
+    /// 取出处理函数开始分发，已经在分发中时返回 None
+    fn begin_call(&mut self, e: &E) -> Option<Vec<(u64, EventHandler<E>)>> {
+        if self.dispatching.is_some() {
+            return None;
+        }
+        if self.event_id.is_none() {
+            self.event_id = Some(e.id().to_owned());
+        }
+
+        let handlers = std::mem::take(&mut self.handlers);
+        self.dispatching = Some(handlers.iter().map(|(id, _)| *id).collect());
+        Some(handlers)
+    }
+
+    /// 放回处理函数，合并分发中的增删
+    fn restore_handlers(&mut self, mut handlers: Vec<(u64, EventHandler<E>)>) {
+        let removed = std::mem::take(&mut self.removed_in_dispatch);
+        handlers.retain(|(id, _)| !removed.contains(id));
+
+        let added = std::mem::replace(&mut self.handlers, handlers);
+        for (id, h) in added {
+            self.insert(id, h);
+        }
+        self.dispatching = None;
+    }
+
+    /// 放回处理函数，更新统计
+    fn end_call(&mut self, handlers: Vec<(u64, EventHandler<E>)>, e: &E, report: CallReport) {
+        self.restore_handlers(handlers);
+
+        if let Some(candidate) = report.slowest {
+            match &self.slowest {
+                Some(slowest) if slowest.id != candidate.id && slowest.max >= candidate.max => {}
+                _ => self.slowest = Some(candidate),
+            }
+        }
+
+        self.samples.record(report.cost);
+        if let Some(level) = self.enabled_log_level() {
+            log::log!(
+                target: EVENT_LOG_TARGET,
+                level,
+                "event ID={} handled by {} handler(s) cost: {}us",
                 e.id(),
-                cost,
-                std::file!(), //TODO: rela filename
-                std::line!()  //TODO: real linenumber
-            )
+                report.handled,
+                report.cost.as_micros()
+            );
+        }
+
+        if report.cost > self.slow_threshold {
+            if let Some((location, handler_cost)) = report.hotspot {
+                log::error!(
+                    target: EVENT_LOG_TARGET,
+                    "call on event ID={} timeout cost: {}ms, hotspot **@{}:{} cost: {}ms",
+                    e.id(),
+                    report.cost.as_millis(),
+                    location.file(),
+                    location.line(),
+                    handler_cost.as_millis()
+                )
+            }
         }
     }
 }
 
+/// 分发中持有取出的处理函数，处理函数 panic 时在 drop 中放回，监听者不会停留在分发状态
+struct DispatchGuard<E, F>
+where
+    E: Event,
+    F: FnOnce(Vec<(u64, EventHandler<E>)>),
+{
+    handlers: Vec<(u64, EventHandler<E>)>,
+    restore: Option<F>,
+}
+
+impl<E, F> DispatchGuard<E, F>
+where
+    E: Event,
+    F: FnOnce(Vec<(u64, EventHandler<E>)>),
+{
+    fn new(handlers: Vec<(u64, EventHandler<E>)>, restore: F) -> Self {
+        Self {
+            handlers,
+            restore: Some(restore),
+        }
+    }
+
+    /// 分发正常结束，取回处理函数
+    fn finish(mut self) -> Vec<(u64, EventHandler<E>)> {
+        self.restore = None;
+        std::mem::take(&mut self.handlers)
+    }
+}
+
+impl<E, F> Drop for DispatchGuard<E, F>
+where
+    E: Event,
+    F: FnOnce(Vec<(u64, EventHandler<E>)>),
+{
+    fn drop(&mut self) {
+        if let Some(restore) = self.restore.take() {
+            restore(std::mem::take(&mut self.handlers));
+        }
+    }
+}
+
+/// 依次调用处理函数，调用期间不持有监听者
+fn run_handlers<E, R, D>(
+    handlers: &mut Vec<(u64, EventHandler<E>)>,
+    e: &E,
+    log_level: Option<log::Level>,
+    is_removed: R,
+    mut on_detach: D,
+) -> CallReport
+where
+    E: Event,
+    R: Fn(u64) -> bool,
+    D: FnMut(u64),
+{
+    let sw = StopWatch::new();
+    let mut report = CallReport {
+        cost: Duration::ZERO,
+        handled: 0,
+        hotspot: None,
+        slowest: None,
+    };
+
+    let mut i = 0;
+    while i < handlers.len() {
+        let (id, h) = &mut handlers[i];
+        if is_removed(*id) {
+            i += 1;
+            continue;
+        }
+
+        let handler_sw = StopWatch::new();
+        let propagation = h.handle(e);
+        let cost = handler_sw.elapsed_duration();
+        h.record(cost);
+        report.handled += 1;
+
+        if let Some(level) = log_level {
+            log::log!(
+                target: EVENT_LOG_TARGET,
+                level,
+                "event ID={} handler @{}:{} cost: {}us propagation: {:?}",
+                e.id(),
+                h.location.file(),
+                h.location.line(),
+                cost.as_micros(),
+                propagation
+            );
+        }
+
+        if report.hotspot.is_none_or(|(_, max)| cost > max) {
+            report.hotspot = Some((h.location, cost));
+        }
+        if report.slowest.as_ref().is_none_or(|s| h.max > s.max) {
+            report.slowest = Some(h.stats(*id));
+        }
+
+        if h.once {
+            let (id, _) = handlers.remove(i);
+            on_detach(id);
+        } else {
+            i += 1;
+        }
+
+        if propagation == Propagation::Stop {
+            break;
+        }
+    }
+
+    report.cost = sw.elapsed_duration();
+    report
+}
+
+/// 分发线程局部监听者上的事件
+///
+/// 分发时处理函数被取出，处理函数里可以增删处理函数或触发其他事件；
+/// 在处理函数里再次触发同一事件时，事件进入 [`EventQueue`]，等待下次 flush。
+pub fn dispatch_event<E>(listener: &'static LocalKey<RefCell<EventListener<E>>>, e: E)
+where
+    E: Event + 'static,
+{
+    if !try_dispatch_event(listener, &e) {
+        log::debug!(
+            target: EVENT_LOG_TARGET,
+            "event ID={} triggered re-entrantly, deferred to event queue",
+            e.id()
+        );
+        EventQueue::push(move || dispatch_event(listener, e));
+    }
+}
+
+// 已经在分发中时不分发并返回 false
+fn try_dispatch_event<E>(listener: &'static LocalKey<RefCell<EventListener<E>>>, e: &E) -> bool
+where
+    E: Event + 'static,
+{
+    let Some(handlers) = listener.with(|g| g.borrow_mut().begin_call(e)) else {
+        return false;
+    };
+
+    let log_level = listener.with(|g| g.borrow().enabled_log_level());
+    let mut guard = DispatchGuard::new(handlers, |handlers| {
+        listener.with(|g| g.borrow_mut().restore_handlers(handlers))
+    });
+    let report = run_handlers(
+        &mut guard.handlers,
+        e,
+        log_level,
+        |id| listener.with(|g| g.borrow().is_removed_in_dispatch(id)),
+        |id| listener.with(|g| g.borrow_mut().detach_in_dispatch(id)),
+    );
+    let handlers = guard.finish();
+    listener.with(|g| g.borrow_mut().end_call(handlers, e, report));
+    true
+}
+
+impl<E> Default for EventListener<E>
+where
+    E: Event,
+{
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
 /// Impl Event trait for struct
+///
+/// 数字 id 由字符串 id 哈希得到，新代码请使用 `#[derive(Event)]`。
 #[macro_export]
 macro_rules! impl_event_for {
     ($s:literal, $t:ident) => {
-        paste::paste! {
-            impl Event for $t {
-                /// Id string
-                fn id(&self) -> &str {
-                    stringify!([<$s _ $t>])
-                }
-                fn add_callback<F>(f: F)
-                where
-                    F: FnMut(&Self) + 'static,
-                {
-                    let h = EventHandler::<Self>::new(f);
-                    [<G_EVENT_LISTENER_ $s:upper _ $t:upper>].with(|g| g.borrow_mut().listen_event(h));
+        impl $crate::Event for $t {
+            const EVENT_NAMESPACE: &'static str = $s;
+            const EVENT_NAME: &'static str = concat!($s, "_", stringify!($t));
+            const EVENT_ID: u32 = $crate::event_id_hash(concat!($s, "_", stringify!($t)));
+
+            fn listener(
+            ) -> &'static std::thread::LocalKey<std::cell::RefCell<$crate::EventListener<Self>>>
+            {
+                thread_local! {
+                    static LISTENER: std::cell::RefCell<$crate::EventListener<$t>> =
+                        std::cell::RefCell::new($crate::EventListener::new_registered());
                 }
-                fn trigger(&mut self) {
-                    [<G_EVENT_LISTENER_ $s:upper _ $t:upper>].with(|g| g.borrow_mut().call(self));
+                &LISTENER
+            }
+        }
+    };
+}
+
+#[cfg(test)]
+mod tests {
+    use std::cell::RefCell;
+    use std::rc::Rc;
+
+    use super::*;
+
+    struct Ping {
+        value: i32,
+    }
+    crate::impl_event_for!("test", Ping);
+
+    type CallLog = Rc<RefCell<Vec<&'static str>>>;
+    type Callback = Box<dyn FnMut(&Ping)>;
+
+    // records the handler name into the call log
+    fn recorder() -> (CallLog, impl Fn(&'static str) -> Callback) {
+        let log = Rc::new(RefCell::new(Vec::new()));
+        let log2 = log.clone();
+        let make = move |name: &'static str| {
+            let log = log2.clone();
+            Box::new(move |_: &Ping| log.borrow_mut().push(name)) as Callback
+        };
+        (log, make)
+    }
+
+    #[test]
+    fn test_event_subscription_remove() {
+        let (log, make) = recorder();
+        let a = Ping::add_callback(make("a"));
+        let _b = Ping::add_callback(make("b"));
+
+        Ping { value: 1 }.trigger();
+        assert_eq!(*log.borrow(), vec!["a", "b"]);
+
+        let id = a.id();
+        assert!(a.remove());
+        assert!(!Ping::remove_callback(id));
+
+        Ping { value: 2 }.trigger();
+        assert_eq!(*log.borrow(), vec!["a", "b", "b"]);
+    }
+
+    #[test]
+    fn test_event_priority_and_once() {
+        let (log, make) = recorder();
+        Ping::add_handler(EventHandler::new(make("low")).priority(-1));
+        Ping::add_handler(EventHandler::new(make("default1")));
+        Ping::add_handler(EventHandler::new(make("high")).priority(10));
+        let once = Ping::add_handler(EventHandler::new(make("once")).priority(5).once());
+        Ping::add_callback(make("default2"));
+
+        Ping { value: 1 }.trigger();
+        assert_eq!(
+            *log.borrow(),
+            vec!["high", "once", "default1", "default2", "low"]
+        );
+
+        log.borrow_mut().clear();
+        Ping { value: 2 }.trigger();
+        assert_eq!(*log.borrow(), vec!["high", "default1", "default2", "low"]);
+
+        // already detached after first fire
+        assert!(!once.remove());
+    }
+
+    #[test]
+    fn test_event_stop_propagation() {
+        let (log, make) = recorder();
+        Ping::add_callback(make("after"));
+
+        let log2 = log.clone();
+        Ping::add_handler(
+            EventHandler::with_propagation(move |e: &Ping| {
+                log2.borrow_mut().push("guard");
+                if e.value < 0 {
+                    Propagation::Stop
+                } else {
+                    Propagation::Continue
                 }
+            })
+            .priority(1),
+        );
+
+        Ping { value: -1 }.trigger();
+        assert_eq!(*log.borrow(), vec!["guard"]);
+
+        Ping { value: 1 }.trigger();
+        assert_eq!(*log.borrow(), vec!["guard", "guard", "after"]);
+    }
+
+    #[test]
+    fn test_event_listener_once_stop() {
+        // a once handler stopping propagation is still detached
+        let mut listener = EventListener::<Ping>::new();
+        let count = Rc::new(RefCell::new(0));
+        let count2 = count.clone();
+        listener.listen_event(EventHandler::with_propagation(|_: &Ping| Propagation::Stop).once());
+        listener.listen_event(EventHandler::new(move |_: &Ping| *count2.borrow_mut() += 1));
+        assert_eq!(listener.len(), 2);
+
+        listener.call(Ping { value: 0 });
+        assert_eq!(*count.borrow(), 0);
+        assert_eq!(listener.len(), 1);
+
+        listener.call(Ping { value: 0 });
+        assert_eq!(*count.borrow(), 1);
+    }
+
+    struct Slow;
+    crate::impl_event_for!("test", Slow);
+
+    #[test]
+    fn test_event_stats() {
+        assert!(Slow::stats().is_none());
+
+        let line = line!() + 1;
+        let fast = Slow::add_callback(|_| {});
+        Slow::add_handler(
+            EventHandler::new(|_: &Slow| std::thread::sleep(Duration::from_millis(3))).priority(-1),
+        );
+        Slow::set_slow_threshold(Duration::from_millis(1));
+
+        for _ in 0..4 {
+            Slow.trigger();
+        }
+
+        let stats = Slow::stats().unwrap();
+        assert_eq!(stats.event_id, "test_Slow");
+        assert_eq!(stats.count, 4);
+        assert!(stats.p50 >= Duration::from_millis(3));
+        assert!(stats.p99 >= stats.p50);
+        assert!(stats.max >= stats.p99);
+        assert_eq!(stats.handlers.len(), 2);
+        assert!(stats.handlers.iter().all(|h| h.count == 4));
+
+        // registration location is the caller, not commlib internals
+        let h = &stats.handlers[0];
+        assert_eq!(h.id, fast.id());
+        assert!(h.location.file().ends_with("commlib_event.rs"));
+        assert_eq!(h.location.line(), line);
+
+        let slowest = stats.slowest_handler.clone().unwrap();
+        assert_eq!(slowest.location.line(), line + 2);
+        assert!(slowest.max >= Duration::from_millis(3));
+
+        // slowest handler survives removal
+        assert!(Slow::remove_callback(slowest.id));
+        let stats = Slow::stats().unwrap();
+        assert_eq!(stats.handlers.len(), 1);
+        assert_eq!(stats.slowest_handler.unwrap().id, slowest.id);
+
+        let dump = crate::dump_event_stats();
+        assert!(dump.iter().any(|s| s.event_id == "test_Slow"));
+        assert!(dump[0].to_string().starts_with("event ID="));
+    }
+
+    #[test]
+    fn test_event_log_records() {
+        crate::test_logger::init();
+
+        let line = line!() + 1;
+        Ping::add_callback(|_| {});
+        Ping::add_handler(
+            EventHandler::with_propagation(|_: &Ping| Propagation::Stop).priority(-1),
+        );
+
+        Ping { value: 1 }.trigger();
+
+        let records: Vec<_> = crate::test_logger::take()
+            .into_iter()
+            .filter(|r| r.target == EVENT_LOG_TARGET)
+            .collect();
+        assert_eq!(records.len(), 3);
+        assert!(records.iter().all(|r| r.level == log::Level::Trace));
+
+        let first = &records[0].message;
+        assert!(first.starts_with("event ID=test_Ping handler @"));
+        assert!(first.contains(&format!("commlib_event.rs:{} cost: ", line)));
+        assert!(first.ends_with("us propagation: Continue"));
+        assert!(records[1].message.ends_with("us propagation: Stop"));
+        assert!(records[2]
+            .message
+            .starts_with("event ID=test_Ping handled by 2 handler(s) cost: "));
+
+        Ping::set_log_level(log::LevelFilter::Debug);
+        Ping { value: 2 }.trigger();
+        let records = crate::test_logger::take();
+        assert_eq!(records.len(), 3);
+        assert!(records.iter().all(|r| r.level == log::Level::Debug));
+
+        Ping::set_log_level(log::LevelFilter::Off);
+        Ping { value: 3 }.trigger();
+        assert!(crate::test_logger::take().is_empty());
+    }
+
+    #[test]
+    fn test_event_post_and_flush() {
+        let values = Rc::new(RefCell::new(Vec::new()));
+        let values2 = values.clone();
+        Ping::add_callback(move |e| {
+            values2.borrow_mut().push(e.value);
+            if e.value == 2 {
+                // posted while flushing: waits for the next flush
+                Ping { value: 3 }.post();
             }
-            thread_local! {
-                static [<G_EVENT_LISTENER_ $s:upper _ $t:upper>]: std::cell::RefCell<EventListener<$t>> = std::cell::RefCell::new(EventListener::<$t>::new());
+        });
+
+        Ping { value: 1 }.post();
+        Ping { value: 2 }.post();
+        assert!(values.borrow().is_empty());
+        assert_eq!(EventQueue::len(), 2);
+
+        assert_eq!(EventQueue::flush(), 2);
+        assert_eq!(*values.borrow(), vec![1, 2]);
+        assert_eq!(EventQueue::len(), 1);
+
+        assert_eq!(EventQueue::flush(), 1);
+        assert_eq!(*values.borrow(), vec![1, 2, 3]);
+        assert!(EventQueue::is_empty());
+    }
+
+    #[test]
+    fn test_event_reentrant_trigger() {
+        let values = Rc::new(RefCell::new(Vec::new()));
+
+        // triggering the same event from its own handler is deferred instead of panicking
+        let values2 = values.clone();
+        Ping::add_callback(move |e| {
+            values2.borrow_mut().push(e.value);
+            if e.value == 1 {
+                Ping { value: 10 }.trigger();
+                Slow.trigger();
+                Ping::with_listener(|l| l.call(Ping { value: 20 }));
             }
+        });
+
+        let values3 = values.clone();
+        Slow::add_callback(move |_| values3.borrow_mut().push(-1));
+
+        Ping { value: 1 }.trigger();
+        // other event types still dispatch synchronously
+        assert_eq!(*values.borrow(), vec![1, -1]);
+        assert_eq!(EventQueue::len(), 2);
+
+        // the nested events arrive after flush, in order
+        assert_eq!(EventQueue::flush(), 2);
+        assert_eq!(*values.borrow(), vec![1, -1, 10, 20]);
+    }
+
+    #[test]
+    fn test_event_handler_panic_recovers() {
+        let values = Rc::new(RefCell::new(Vec::new()));
+
+        let values2 = values.clone();
+        Ping::add_callback(move |e| values2.borrow_mut().push(e.value));
+        Ping::add_callback(|e| {
+            if e.value == 1 {
+                panic!("handler panic");
+            }
+        });
+        let values3 = values.clone();
+        Ping::add_callback(move |e| values3.borrow_mut().push(e.value * 100));
+
+        let result = std::panic::catch_unwind(|| Ping { value: 1 }.trigger());
+        assert!(result.is_err());
+        assert!(!Ping::with_listener(|l| l.is_dispatching()));
+        assert_eq!(Ping::with_listener(|l| l.len()), 3);
+
+        // handlers are back in place and the next trigger is not deferred
+        Ping { value: 2 }.trigger();
+        assert_eq!(*values.borrow(), vec![1, 2, 200]);
+        assert_eq!(EventQueue::len(), 0);
+
+        // same for a listener called directly
+        let mut listener = EventListener::<Ping>::new();
+        listener.listen_event(EventHandler::new(|_: &Ping| panic!("handler panic")));
+        let result = std::panic::catch_unwind(std::panic::AssertUnwindSafe(|| {
+            listener.call(Ping { value: 1 })
+        }));
+        assert!(result.is_err());
+        assert!(!listener.is_dispatching());
+        assert_eq!(listener.len(), 1);
+    }
+
+    #[test]
+    fn test_event_subscribe_in_handler() {
+        let values = Rc::new(RefCell::new(Vec::new()));
+
+        // a handler removing itself and adding another one during dispatch
+        let sub: Rc<RefCell<Option<EventSubscription>>> = Rc::new(RefCell::new(None));
+        let sub2 = sub.clone();
+        let values2 = values.clone();
+        *sub.borrow_mut() = Some(Ping::add_callback(move |e| {
+            values2.borrow_mut().push(e.value);
+            assert!(sub2.borrow_mut().take().unwrap().remove());
+
+            let values3 = values2.clone();
+            Ping::add_callback(move |e| values3.borrow_mut().push(e.value * 100));
+        }));
+
+        // removed during dispatch by an earlier handler: not called
+        let later: Rc<RefCell<Option<EventSubscription>>> = Rc::new(RefCell::new(None));
+        let later2 = later.clone();
+        Ping::add_handler(
+            EventHandler::new(move |_: &Ping| {
+                if let Some(sub) = later2.borrow_mut().take() {
+                    assert!(sub.remove());
+                }
+            })
+            .priority(1),
+        );
+        let values4 = values.clone();
+        *later.borrow_mut() = Some(Ping::add_callback(move |_| values4.borrow_mut().push(-1)));
+
+        assert_eq!(Ping::with_listener(|l| l.len()), 3);
+        Ping { value: 1 }.trigger();
+        assert_eq!(*values.borrow(), vec![1]);
+        assert_eq!(Ping::with_listener(|l| l.len()), 2);
+
+        Ping { value: 2 }.trigger();
+        assert_eq!(*values.borrow(), vec![1, 200]);
+    }
+
+    mod derived {
+        // no imports: the derive must be hygienic
+        #[derive(crate::Event)]
+        #[event(namespace = "test", id = 42)]
+        pub struct Explicit {
+            pub value: i32,
         }
-    };
+
+        #[derive(crate::Event)]
+        #[event(namespace = "test")]
+        pub struct Hashed;
+
+        // same numeric id in another namespace is fine
+        #[derive(crate::Event)]
+        #[event(namespace = "other", id = 42)]
+        pub struct OtherExplicit;
+
+        #[derive(crate::Event)]
+        #[event(namespace = "test", id = 42)]
+        pub struct SameId;
+
+        crate::assert_unique_event_ids!(Explicit, Hashed, OtherExplicit, super::Ping, super::Slow);
+    }
+
+    #[test]
+    fn test_event_derive() {
+        use derived::{Explicit, Hashed, OtherExplicit};
+
+        assert_eq!(Explicit::EVENT_ID, 42);
+        assert_eq!(Explicit::EVENT_NAMESPACE, "test");
+        assert_eq!(Explicit::EVENT_NAME, "test_Explicit");
+        assert_eq!(Explicit { value: 0 }.id(), "test_Explicit");
+        assert_eq!(OtherExplicit::EVENT_ID, 42);
+
+        assert!(!event_ids_collide::<Explicit, OtherExplicit>());
+        assert!(event_ids_collide::<Explicit, derived::SameId>());
+        assert!(event_ids_collide::<Ping, Ping>());
+
+        // hashed ids are the same for the derive and impl_event_for!
+        assert_eq!(Hashed::EVENT_ID, event_id_hash("test_Hashed"));
+        assert_eq!(Ping::EVENT_ID, event_id_hash("test_Ping"));
+        assert_eq!(event_id_hash(""), 0x811c_9dc5);
+        assert_eq!(event_id_hash("a"), 0xe40c_292c);
+
+        let values = Rc::new(RefCell::new(Vec::new()));
+        let values2 = values.clone();
+        Explicit::add_callback(move |e| values2.borrow_mut().push(e.value));
+        Explicit { value: 3 }.trigger();
+        Explicit { value: 4 }.post();
+        EventQueue::flush();
+        assert_eq!(*values.borrow(), vec![3, 4]);
+        assert_eq!(Explicit::stats().unwrap().count, 2);
+    }
 }
@@ -0,0 +1,425 @@
+//!
+//! Common Library: shared event bus
+//!
+//! impl_event_for! 的监听者是 thread_local 的，只在触发线程上分发。
+//! SharedEventBus 的处理函数注册时绑定一个目标执行器（比如 ThreadPool 的某个 slot），
+//! 任何线程 trigger 都会把事件投递到处理函数所在的执行器上执行。
+//!
+
+use parking_lot::{Mutex, RwLock};
+use std::collections::{HashMap, VecDeque};
+use std::sync::Arc;
+use std::thread::{self, ThreadId};
+
+use crate::utils::ThreadPool;
+
+type Job = Box<dyn FnOnce() + Send + 'static>;
+
+/// 事件处理函数的执行器
+pub trait EventExecutor: Send + Sync {
+    /// 把任务投递到执行器
+    fn execute(&self, job: Job);
+}
+
+/// 在触发线程上直接执行
+pub struct ImmediateExecutor;
+
+impl EventExecutor for ImmediateExecutor {
+    #[inline(always)]
+    fn execute(&self, job: Job) {
+        job();
+    }
+}
+
+/// ThreadPool 的固定 slot，保证同一个 slot 上的处理函数串行执行
+pub struct ThreadPoolSlot {
+    pool: Arc<ThreadPool>,
+    pos: usize,
+}
+
+impl ThreadPoolSlot {
+    /// 绑定 pool 的第 pos 个 slot
+    pub fn new(pool: Arc<ThreadPool>, pos: usize) -> Self {
+        Self { pool, pos }
+    }
+}
+
+impl EventExecutor for ThreadPoolSlot {
+    #[inline(always)]
+    fn execute(&self, job: Job) {
+        self.pool.execute(self.pos, job);
+    }
+}
+
+/// 收件箱：事件先排队，由拥有者线程（比如主线程的游戏循环）调用 run_pending 执行
+#[derive(Default)]
+pub struct EventInbox {
+    jobs: Mutex<VecDeque<Job>>,
+}
+
+impl EventInbox {
+    /// 空收件箱
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// 执行所有排队的任务，返回执行的数量
+    pub fn run_pending(&self) -> usize {
+        let mut count = 0;
+        loop {
+            // 不持锁执行，处理函数里可以再次 trigger
+            let job = self.jobs.lock().pop_front();
+            match job {
+                Some(job) => {
+                    job();
+                    count += 1;
+                }
+                None => break,
+            }
+        }
+        count
+    }
+
+    /// 排队中的任务数量
+    pub fn len(&self) -> usize {
+        self.jobs.lock().len()
+    }
+
+    /// 是否没有排队的任务
+    pub fn is_empty(&self) -> bool {
+        self.jobs.lock().is_empty()
+    }
+}
+
+impl EventExecutor for EventInbox {
+    #[inline(always)]
+    fn execute(&self, job: Job) {
+        self.jobs.lock().push_back(job);
+    }
+}
+
+type SharedEventFn<E> = Arc<SharedEventFunc<E>>;
+type BoxedEventFn<E> = Box<dyn FnMut(&E) + Send>;
+
+// 处理函数同一时间只在一个线程上执行。处理函数正在执行时（其他线程，或者在处理函数里间接触发），
+// 事件排进 deferred 由正在执行的线程处理，不会等锁，所以不会互相死锁
+struct SharedEventFunc<E> {
+    func: Mutex<BoxedEventFn<E>>,
+    deferred: Mutex<VecDeque<Arc<E>>>,
+}
+
+impl<E> SharedEventFunc<E> {
+    fn new<F>(f: F) -> Self
+    where
+        F: FnMut(&E) + Send + 'static,
+    {
+        Self {
+            func: Mutex::new(Box::new(f)),
+            deferred: Mutex::new(VecDeque::new()),
+        }
+    }
+
+    fn call(&self, e: Arc<E>) {
+        self.deferred.lock().push_back(e);
+        loop {
+            let Some(mut func) = self.func.try_lock() else {
+                return;
+            };
+            loop {
+                let next = self.deferred.lock().pop_front();
+                match next {
+                    Some(e) => func(&e),
+                    None => break,
+                }
+            }
+            drop(func);
+
+            // 解锁前其他线程排进来的事件
+            if self.deferred.lock().is_empty() {
+                return;
+            }
+        }
+    }
+}
+
+struct SharedEventHandler<E> {
+    executor: Arc<dyn EventExecutor>,
+    func: SharedEventFn<E>,
+}
+
+/// 跨线程的事件总线
+pub struct SharedEventBus<E> {
+    handlers: RwLock<Vec<SharedEventHandler<E>>>,
+    // 正在分发的线程，分发中再次触发的事件排在后面
+    dispatching: Mutex<HashMap<ThreadId, VecDeque<E>>>,
+}
+
+impl<E> SharedEventBus<E>
+where
+    E: Send + Sync + 'static,
+{
+    /// 空总线
+    pub fn new() -> Self {
+        Self {
+            handlers: RwLock::new(Vec::new()),
+            dispatching: Mutex::new(HashMap::new()),
+        }
+    }
+
+    /// 注册处理函数，事件总是在 executor 上处理
+    pub fn listen_event<F>(&self, executor: Arc<dyn EventExecutor>, f: F)
+    where
+        F: FnMut(&E) + Send + 'static,
+    {
+        self.handlers.write().push(SharedEventHandler {
+            executor,
+            func: Arc::new(SharedEventFunc::new(f)),
+        });
+    }
+
+    /// 把事件投递给所有处理函数，可以在任意线程调用
+    ///
+    /// 在处理函数里再次触发同一事件时，事件在当前事件分发给所有处理函数之后再分发，
+    /// 每个处理函数看到的事件顺序一致。
+    ///
+    /// 处理函数正在其他线程上执行时，事件交给那个线程执行，ImmediateExecutor 的处理函数
+    /// 不一定在触发线程上执行。
+    pub fn call(&self, e: E) {
+        let current = thread::current().id();
+        {
+            let mut dispatching = self.dispatching.lock();
+            if let Some(queue) = dispatching.get_mut(&current) {
+                queue.push_back(e);
+                return;
+            }
+            dispatching.insert(current, VecDeque::new());
+        }
+        // 处理函数 panic 时也要清除，否则之后的事件一直被延迟
+        let _dispatching = DispatchingGuard {
+            dispatching: &self.dispatching,
+            thread: current,
+        };
+
+        let mut next = Some(e);
+        while let Some(e) = next {
+            self.deliver(e);
+            next = self
+                .dispatching
+                .lock()
+                .get_mut(&current)
+                .and_then(|queue| queue.pop_front());
+        }
+    }
+
+    fn deliver(&self, e: E) {
+        let targets: Vec<(Arc<dyn EventExecutor>, SharedEventFn<E>)> = self
+            .handlers
+            .read()
+            .iter()
+            .map(|h| (h.executor.clone(), h.func.clone()))
+            .collect();
+
+        let e = Arc::new(e);
+        for (executor, func) in targets {
+            let e = e.clone();
+            executor.execute(Box::new(move || func.call(e)));
+        }
+    }
+
+    /// 处理函数数量
+    pub fn len(&self) -> usize {
+        self.handlers.read().len()
+    }
+
+    /// 是否没有处理函数
+    pub fn is_empty(&self) -> bool {
+        self.handlers.read().is_empty()
+    }
+}
+
+struct DispatchingGuard<'a, E> {
+    dispatching: &'a Mutex<HashMap<ThreadId, VecDeque<E>>>,
+    thread: ThreadId,
+}
+
+impl<E> Drop for DispatchingGuard<'_, E> {
+    fn drop(&mut self) {
+        self.dispatching.lock().remove(&self.thread);
+    }
+}
+
+impl<E> Default for SharedEventBus<E>
+where
+    E: Send + Sync + 'static,
+{
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// 跨线程分发的事件
+pub trait SharedEvent: Send + Sync + Sized + 'static {
+    /// Id string
+    fn id(&self) -> &str;
+
+    /// 进程内唯一的事件总线
+    fn bus() -> &'static SharedEventBus<Self>;
+
+    /// Add callback running on `executor`
+    fn add_callback_on<F>(executor: Arc<dyn EventExecutor>, f: F)
+    where
+        F: FnMut(&Self) + Send + 'static,
+    {
+        Self::bus().listen_event(executor, f);
+    }
+
+    /// Trigger event from any thread
+    fn trigger_shared(self) {
+        Self::bus().call(self);
+    }
+}
+
+/// Impl SharedEvent trait for struct
+#[macro_export]
+macro_rules! impl_shared_event_for {
+    ($s:literal, $t:ident) => {
+        paste::paste! {
+            impl $crate::SharedEvent for $t {
+                /// Id string
+                fn id(&self) -> &str {
+                    stringify!([<$s _ $t>])
+                }
+                fn bus() -> &'static $crate::SharedEventBus<Self> {
+                    static BUS: std::sync::OnceLock<$crate::SharedEventBus<$t>> = std::sync::OnceLock::new();
+                    BUS.get_or_init($crate::SharedEventBus::new)
+                }
+            }
+        }
+    };
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::atomic::{AtomicUsize, Ordering};
+    use std::thread;
+
+    use super::*;
+    use crate::utils::ThreadPoolBuilder;
+
+    struct Hello {
+        value: usize,
+    }
+    crate::impl_shared_event_for!("test", Hello);
+
+    #[test]
+    fn test_shared_event_on_pool_slot() {
+        let pool = Arc::new(ThreadPoolBuilder::new().num_threads(2).build());
+        let slot: Arc<dyn EventExecutor> = Arc::new(ThreadPoolSlot::new(pool.clone(), 1));
+
+        let (tx, rx) = std::sync::mpsc::channel();
+        let registered_on = thread::current().id();
+        Hello::add_callback_on(slot, move |e| {
+            tx.send((e.value, thread::current().id())).unwrap();
+        });
+
+        // trigger from another thread
+        thread::spawn(|| Hello { value: 7 }.trigger_shared())
+            .join()
+            .unwrap();
+        Hello { value: 8 }.trigger_shared();
+        pool.join();
+
+        let received: Vec<_> = rx.try_iter().collect();
+        assert_eq!(received.len(), 2);
+        assert_eq!(received[0].0, 7);
+        assert_eq!(received[1].0, 8);
+        // both delivered on the same pool thread
+        assert_eq!(received[0].1, received[1].1);
+        assert_ne!(received[0].1, registered_on);
+    }
+
+    #[test]
+    fn test_shared_event_reentrant() {
+        let bus = Arc::new(SharedEventBus::<usize>::new());
+        let values = Arc::new(Mutex::new(Vec::new()));
+
+        let bus2 = Arc::downgrade(&bus);
+        let values2 = values.clone();
+        bus.listen_event(Arc::new(ImmediateExecutor), move |e| {
+            values2.lock().push(*e);
+            if *e < 3 {
+                // deferred until every handler got this event, instead of deadlocking
+                bus2.upgrade().unwrap().call(*e + 1);
+                values2.lock().push(*e * 10);
+            }
+        });
+        let values3 = values.clone();
+        bus.listen_event(Arc::new(ImmediateExecutor), move |e| {
+            values3.lock().push(*e * 100);
+        });
+
+        bus.call(1);
+        assert_eq!(*values.lock(), vec![1, 10, 100, 2, 20, 200, 3, 300]);
+    }
+
+    struct Ping(usize);
+    crate::impl_shared_event_for!("test", Ping);
+
+    struct Pong(usize);
+    crate::impl_shared_event_for!("test", Pong);
+
+    #[test]
+    fn test_shared_event_cross_thread_reentrant() {
+        // immediate handlers triggering each other from two threads don't deadlock
+        let count = Arc::new(AtomicUsize::new(0));
+        let c = count.clone();
+        Ping::add_callback_on(Arc::new(ImmediateExecutor), move |e| {
+            c.fetch_add(1, Ordering::SeqCst);
+            if e.0 < 100 {
+                Pong(e.0 + 1).trigger_shared();
+            }
+        });
+        let c = count.clone();
+        Pong::add_callback_on(Arc::new(ImmediateExecutor), move |e| {
+            c.fetch_add(1, Ordering::SeqCst);
+            if e.0 < 100 {
+                Ping(e.0 + 1).trigger_shared();
+            }
+        });
+
+        thread::scope(|s| {
+            s.spawn(|| (0..20).for_each(|_| Ping(0).trigger_shared()));
+            s.spawn(|| (0..20).for_each(|_| Pong(0).trigger_shared()));
+        });
+        assert_eq!(count.load(Ordering::SeqCst), 2 * 20 * 101);
+    }
+
+    #[test]
+    fn test_shared_event_inbox() {
+        let bus = SharedEventBus::<usize>::new();
+        let inbox = Arc::new(EventInbox::new());
+        let sum = Arc::new(AtomicUsize::new(0));
+
+        let sum2 = sum.clone();
+        bus.listen_event(inbox.clone(), move |e| {
+            sum2.fetch_add(*e, Ordering::SeqCst);
+        });
+        let sum3 = sum.clone();
+        bus.listen_event(Arc::new(ImmediateExecutor), move |e| {
+            sum3.fetch_add(*e * 100, Ordering::SeqCst);
+        });
+        assert_eq!(bus.len(), 2);
+
+        thread::scope(|s| {
+            s.spawn(|| bus.call(1));
+            s.spawn(|| bus.call(2));
+        });
+
+        // immediate handlers ran on the triggering threads, inbox waits for its owner
+        assert_eq!(sum.load(Ordering::SeqCst), 300);
+        assert_eq!(inbox.len(), 2);
+        assert_eq!(inbox.run_pending(), 2);
+        assert_eq!(sum.load(Ordering::SeqCst), 303);
+        assert!(inbox.is_empty());
+    }
+}
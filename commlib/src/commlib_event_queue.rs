@@ -0,0 +1,50 @@
+//!
+//! Common Library: event queue
+//!
+//! 延迟事件队列：post 的事件以及处理函数里重入触发的事件先进入线程局部队列，
+//! 由游戏循环在 Clock::update 旁边调用 EventQueue::flush 统一触发。
+//!
+
+use std::cell::RefCell;
+use std::collections::VecDeque;
+
+type DeferredEvent = Box<dyn FnOnce()>;
+
+thread_local! {
+    static G_EVENT_QUEUE: RefCell<VecDeque<DeferredEvent>> = const { RefCell::new(VecDeque::new()) };
+}
+
+/// 当前线程的延迟事件队列
+pub struct EventQueue;
+
+impl EventQueue {
+    /// 入队，在下次 flush 时执行
+    pub fn push<F>(f: F)
+    where
+        F: FnOnce() + 'static,
+    {
+        G_EVENT_QUEUE.with(|q| q.borrow_mut().push_back(Box::new(f)));
+    }
+
+    /// 触发 flush 开始时已经排队的事件，返回触发的数量
+    ///
+    /// flush 期间新入队的事件留到下次 flush，避免处理函数互相 post 时死循环。
+    pub fn flush() -> usize {
+        let events = G_EVENT_QUEUE.with(|q| std::mem::take(&mut *q.borrow_mut()));
+        let count = events.len();
+        for f in events {
+            f();
+        }
+        count
+    }
+
+    /// 排队中的事件数量
+    pub fn len() -> usize {
+        G_EVENT_QUEUE.with(|q| q.borrow().len())
+    }
+
+    /// 是否没有排队的事件
+    pub fn is_empty() -> bool {
+        G_EVENT_QUEUE.with(|q| q.borrow().is_empty())
+    }
+}
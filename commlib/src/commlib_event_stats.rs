@@ -0,0 +1,166 @@
+//!
+//! Common Library: event stats
+//!
+//! 每个事件类型的调用统计：次数，p50/p99，最大耗时，最慢的处理函数。
+//! 统计跟随监听者，同样是 thread_local 的，dump_event_stats 只导出当前线程。
+//!
+
+use std::cell::RefCell;
+use std::collections::VecDeque;
+use std::fmt;
+use std::panic::Location;
+use std::time::Duration;
+
+/// 默认的慢事件阈值
+pub const DEFAULT_EVENT_SLOW_THRESHOLD: Duration = Duration::from_millis(10);
+
+/// 计算分位数保留的最近调用次数
+const SAMPLE_WINDOW: usize = 1024;
+
+/// 单个处理函数的统计
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct HandlerStats {
+    /// 订阅 id
+    pub id: u64,
+    /// 注册位置
+    pub location: &'static Location<'static>,
+    pub count: u64,
+    pub total: Duration,
+    pub max: Duration,
+}
+
+/// 单个事件类型的统计
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct EventStats {
+    pub event_id: String,
+    /// 触发次数
+    pub count: u64,
+    /// 最近 SAMPLE_WINDOW 次触发的耗时分位数
+    pub p50: Duration,
+    pub p99: Duration,
+    /// 单次触发的最大耗时
+    pub max: Duration,
+    /// 单次调用耗时最大的处理函数（可能已经移除）
+    pub slowest_handler: Option<HandlerStats>,
+    /// 当前注册的处理函数
+    pub handlers: Vec<HandlerStats>,
+}
+
+impl fmt::Display for EventStats {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "event ID={} count={} p50={}us p99={}us max={}us",
+            self.event_id,
+            self.count,
+            self.p50.as_micros(),
+            self.p99.as_micros(),
+            self.max.as_micros()
+        )?;
+        if let Some(h) = &self.slowest_handler {
+            write!(
+                f,
+                " slowest handler @{}:{} max={}us",
+                h.location.file(),
+                h.location.line(),
+                h.max.as_micros()
+            )?;
+        }
+        Ok(())
+    }
+}
+
+/// 触发耗时采样
+#[derive(Default)]
+pub(crate) struct CallSamples {
+    count: u64,
+    max: Duration,
+    window: VecDeque<Duration>,
+}
+
+impl CallSamples {
+    pub(crate) fn record(&mut self, cost: Duration) {
+        self.count += 1;
+        self.max = std::cmp::max(self.max, cost);
+        if self.window.len() == SAMPLE_WINDOW {
+            self.window.pop_front();
+        }
+        self.window.push_back(cost);
+    }
+
+    pub(crate) fn count(&self) -> u64 {
+        self.count
+    }
+
+    pub(crate) fn max(&self) -> Duration {
+        self.max
+    }
+
+    /// (p50, p99)
+    pub(crate) fn percentiles(&self) -> (Duration, Duration) {
+        let mut sorted: Vec<Duration> = self.window.iter().copied().collect();
+        sorted.sort_unstable();
+        (percentile(&sorted, 0.50), percentile(&sorted, 0.99))
+    }
+}
+
+fn percentile(sorted: &[Duration], q: f64) -> Duration {
+    if sorted.is_empty() {
+        return Duration::ZERO;
+    }
+    let idx = ((sorted.len() - 1) as f64 * q).round() as usize;
+    sorted[idx]
+}
+
+type StatsFn = fn() -> Option<EventStats>;
+
+thread_local! {
+    static G_EVENT_STATS_REGISTRY: RefCell<Vec<StatsFn>> = const { RefCell::new(Vec::new()) };
+}
+
+/// 监听者创建时登记，用于 dump_event_stats
+#[doc(hidden)]
+pub fn register_event_stats(f: StatsFn) {
+    G_EVENT_STATS_REGISTRY.with(|g| g.borrow_mut().push(f));
+}
+
+/// 导出当前线程所有已触发过的事件统计，按最大耗时降序
+///
+/// 不能在事件处理函数里调用。
+pub fn dump_event_stats() -> Vec<EventStats> {
+    let fns = G_EVENT_STATS_REGISTRY.with(|g| g.borrow().clone());
+    let mut stats: Vec<EventStats> = fns.into_iter().filter_map(|f| f()).collect();
+    stats.sort_by_key(|s| std::cmp::Reverse(s.max));
+    stats
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_call_samples() {
+        let mut samples = CallSamples::default();
+        assert_eq!(samples.percentiles(), (Duration::ZERO, Duration::ZERO));
+
+        for i in 1..=100 {
+            samples.record(Duration::from_micros(i));
+        }
+        assert_eq!(samples.count(), 100);
+        assert_eq!(samples.max(), Duration::from_micros(100));
+
+        let (p50, p99) = samples.percentiles();
+        assert_eq!(p50, Duration::from_micros(51));
+        assert_eq!(p99, Duration::from_micros(99));
+
+        // only the latest samples count for percentiles
+        for _ in 0..SAMPLE_WINDOW {
+            samples.record(Duration::from_micros(1));
+        }
+        assert_eq!(
+            samples.percentiles(),
+            (Duration::from_micros(1), Duration::from_micros(1))
+        );
+        assert_eq!(samples.max(), Duration::from_micros(100));
+    }
+}
@@ -0,0 +1,354 @@
+//!
+//! Cron schedule
+//!
+//! 支持 5 段 "分 时 日 月 周" 和 6 段 "秒 分 时 日 月 周"，以及 @daily, @weekly 等缩写。
+//! 每段可以是 `*`, `?`, 数字, 列表 `a,b`, 范围 `a-b`, 步长 `*/n` `a-b/n` `a/n`；
+//! 月份和星期可以用英文缩写（JAN, MON）。星期 0 和 7 都是周日。
+//! 日和星期都有限制时，满足其中之一即可（和 crontab 一致）。
+//!
+
+use std::str::FromStr;
+
+const SECS_PER_DAY: i64 = 86400;
+
+// 最多向后搜索的秒数，找不到说明表达式永远不会触发（比如 2 月 30 日）
+const SEARCH_LIMIT: i64 = 8 * 366 * SECS_PER_DAY;
+
+const MONTH_NAMES: [&str; 12] = [
+    "JAN", "FEB", "MAR", "APR", "MAY", "JUN", "JUL", "AUG", "SEP", "OCT", "NOV", "DEC",
+];
+const WEEKDAY_NAMES: [&str; 7] = ["SUN", "MON", "TUE", "WED", "THU", "FRI", "SAT"];
+
+/// 解析后的 cron 表达式
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct CronSchedule {
+    seconds: u64,
+    minutes: u64,
+    hours: u64,
+    days: u64,
+    months: u64,
+    weekdays: u64,
+    // 日，星期是否为 `*`
+    any_day: bool,
+    any_weekday: bool,
+}
+
+impl CronSchedule {
+    /// 解析 cron 表达式
+    pub fn parse(expr: &str) -> Result<Self, String> {
+        let expr = expr.trim();
+        let expanded = match expr {
+            "@yearly" | "@annually" => "0 0 1 1 *",
+            "@monthly" => "0 0 1 * *",
+            "@weekly" => "0 0 * * 0",
+            "@daily" | "@midnight" => "0 0 * * *",
+            "@hourly" => "0 * * * *",
+            _ => expr,
+        };
+
+        let fields: Vec<&str> = expanded.split_whitespace().collect();
+        let (sec, rest) = match fields.len() {
+            5 => ("0", &fields[..]),
+            6 => (fields[0], &fields[1..]),
+            n => {
+                return Err(format!(
+                    "cron expression \"{}\" has {} fields, expected 5 or 6",
+                    expr, n
+                ))
+            }
+        };
+
+        let weekdays = parse_field(rest[4], 0, 7, &WEEKDAY_NAMES, 0)?;
+        // 7 is sunday too
+        let weekdays = (weekdays & 0x7f) | ((weekdays >> 7) & 1);
+
+        Ok(Self {
+            seconds: parse_field(sec, 0, 59, &[], 0)?,
+            minutes: parse_field(rest[0], 0, 59, &[], 0)?,
+            hours: parse_field(rest[1], 0, 23, &[], 0)?,
+            days: parse_field(rest[2], 1, 31, &[], 0)?,
+            months: parse_field(rest[3], 1, 12, &MONTH_NAMES, 1)?,
+            weekdays,
+            any_day: is_any(rest[2]),
+            any_weekday: is_any(rest[4]),
+        })
+    }
+
+    /// 每天 hh:mm:ss
+    pub fn daily(hh: u32, mm: u32, ss: u32) -> Result<Self, String> {
+        Self::parse(&format!("{} {} {} * * *", ss, mm, hh))
+    }
+
+    /// 每周 weekday (0 = 周日, 1 = 周一 ... 6 = 周六) 的 hh:mm:ss
+    pub fn weekly(weekday: u32, hh: u32, mm: u32, ss: u32) -> Result<Self, String> {
+        Self::parse(&format!("{} {} {} * * {}", ss, mm, hh, weekday))
+    }
+
+    /// 严格晚于 unix_ms 的下一个触发时间（unix ms），tz_offset 是时区偏移（秒，东八区为 8 * 3600）
+    ///
+    /// 永远不会触发时返回 None。
+    pub fn next_after(&self, unix_ms: u64, tz_offset: i32) -> Option<u64> {
+        let tz = tz_offset as i64;
+        let mut t = (unix_ms / 1000) as i64 + 1 + tz;
+        let limit = t + SEARCH_LIMIT;
+
+        while t < limit {
+            let days = t.div_euclid(SECS_PER_DAY);
+            let secs = t.rem_euclid(SECS_PER_DAY);
+            let (year, month, day) = civil_from_days(days);
+
+            if !has(self.months, month) {
+                // first day of next month
+                let (y, m) = if month == 12 {
+                    (year + 1, 1)
+                } else {
+                    (year, month + 1)
+                };
+                t = days_from_civil(y, m, 1) * SECS_PER_DAY;
+                continue;
+            }
+            if !self.day_matches(day, weekday_from_days(days)) {
+                t = (days + 1) * SECS_PER_DAY;
+                continue;
+            }
+
+            let (hour, minute, second) = (secs / 3600, secs / 60 % 60, secs % 60);
+            if !has(self.hours, hour as u32) {
+                t = days * SECS_PER_DAY + (hour + 1) * 3600;
+                continue;
+            }
+            if !has(self.minutes, minute as u32) {
+                t = days * SECS_PER_DAY + hour * 3600 + (minute + 1) * 60;
+                continue;
+            }
+            if !has(self.seconds, second as u32) {
+                t += 1;
+                continue;
+            }
+
+            let unix = t - tz;
+            return if unix >= 0 {
+                Some(unix as u64 * 1000)
+            } else {
+                None
+            };
+        }
+        None
+    }
+
+    #[inline(always)]
+    fn day_matches(&self, day: u32, weekday: u32) -> bool {
+        let day_ok = has(self.days, day);
+        let weekday_ok = has(self.weekdays, weekday);
+        if !self.any_day && !self.any_weekday {
+            day_ok || weekday_ok
+        } else {
+            day_ok && weekday_ok
+        }
+    }
+}
+
+impl FromStr for CronSchedule {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        Self::parse(s)
+    }
+}
+
+#[inline(always)]
+fn has(bits: u64, n: u32) -> bool {
+    bits & (1u64 << n) != 0
+}
+
+#[inline(always)]
+fn is_any(field: &str) -> bool {
+    field == "*" || field == "?"
+}
+
+// names[i] is the value `base + i`
+fn parse_value(s: &str, names: &[&str], base: u32) -> Result<u32, String> {
+    if let Ok(v) = s.parse::<u32>() {
+        return Ok(v);
+    }
+    let upper = s.to_ascii_uppercase();
+    match names.iter().position(|n| *n == upper) {
+        Some(i) => Ok(base + i as u32),
+        None => Err(format!("invalid cron value \"{}\"", s)),
+    }
+}
+
+fn parse_field(field: &str, min: u32, max: u32, names: &[&str], base: u32) -> Result<u64, String> {
+    let mut bits = 0u64;
+    for item in field.split(',') {
+        let (range, step) = match item.split_once('/') {
+            Some((range, step)) => {
+                let step = step
+                    .parse::<u32>()
+                    .map_err(|_| format!("invalid cron step \"{}\"", item))?;
+                if step == 0 {
+                    return Err(format!("invalid cron step \"{}\"", item));
+                }
+                (range, Some(step))
+            }
+            None => (item, None),
+        };
+
+        let (lo, hi) = if is_any(range) {
+            (min, max)
+        } else if let Some((lo, hi)) = range.split_once('-') {
+            (parse_value(lo, names, base)?, parse_value(hi, names, base)?)
+        } else {
+            let v = parse_value(range, names, base)?;
+            // "a/n" runs from a to the end
+            (v, if step.is_some() { max } else { v })
+        };
+
+        if lo < min || hi > max || lo > hi {
+            return Err(format!(
+                "cron field \"{}\" is out of range [{}, {}]",
+                item, min, max
+            ));
+        }
+
+        let step = step.unwrap_or(1);
+        let mut v = lo;
+        while v <= hi {
+            bits |= 1u64 << v;
+            v += step;
+        }
+    }
+    Ok(bits)
+}
+
+// days since 1970-01-01 -> (year, month, day)
+fn civil_from_days(days: i64) -> (i64, u32, u32) {
+    let z = days + 719468;
+    let era = z.div_euclid(146097);
+    let doe = z.rem_euclid(146097);
+    let yoe = (doe - doe / 1460 + doe / 36524 - doe / 146096) / 365;
+    let doy = doe - (365 * yoe + yoe / 4 - yoe / 100);
+    let mp = (5 * doy + 2) / 153;
+    let day = (doy - (153 * mp + 2) / 5 + 1) as u32;
+    let month = if mp < 10 { mp + 3 } else { mp - 9 } as u32;
+    let year = yoe + era * 400 + if month <= 2 { 1 } else { 0 };
+    (year, month, day)
+}
+
+// (year, month, day) -> days since 1970-01-01
+fn days_from_civil(year: i64, month: u32, day: u32) -> i64 {
+    let y = if month <= 2 { year - 1 } else { year };
+    let era = y.div_euclid(400);
+    let yoe = y.rem_euclid(400);
+    let m = month as i64;
+    let doy = (153 * (if m > 2 { m - 3 } else { m + 9 }) + 2) / 5 + day as i64 - 1;
+    let doe = yoe * 365 + yoe / 4 - yoe / 100 + doy;
+    era * 146097 + doe - 719468
+}
+
+// 0 = sunday, 1970-01-01 is a thursday
+#[inline(always)]
+fn weekday_from_days(days: i64) -> u32 {
+    (days + 4).rem_euclid(7) as u32
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const TZ8: i32 = 8 * 3600;
+
+    // unix ms of a local date time at UTC+8
+    fn local_ms(y: i64, mo: u32, d: u32, h: i64, mi: i64, s: i64) -> u64 {
+        let t = days_from_civil(y, mo, d) * SECS_PER_DAY + h * 3600 + mi * 60 + s - TZ8 as i64;
+        t as u64 * 1000
+    }
+
+    #[test]
+    fn test_civil_days() {
+        assert_eq!(civil_from_days(0), (1970, 1, 1));
+        assert_eq!(days_from_civil(2024, 2, 29), 19782);
+        assert_eq!(civil_from_days(19782), (2024, 2, 29));
+        assert_eq!(civil_from_days(-1), (1969, 12, 31));
+        // 2026-10-19 is a monday
+        assert_eq!(weekday_from_days(days_from_civil(2026, 10, 19)), 1);
+    }
+
+    #[test]
+    fn test_cron_parse() {
+        assert!(CronSchedule::parse("0 5 * * *").is_ok());
+        assert!(CronSchedule::parse("*/15 0 5 * * MON-FRI").is_ok());
+        assert!(CronSchedule::parse("@weekly").is_ok());
+        assert_eq!(
+            "0 0 * * 7".parse::<CronSchedule>(),
+            "0 0 * * sun".parse::<CronSchedule>()
+        );
+
+        assert!(CronSchedule::parse("").is_err());
+        assert!(CronSchedule::parse("* * * *").is_err());
+        assert!(CronSchedule::parse("60 * * * *").is_err());
+        assert!(CronSchedule::parse("* 24 * * *").is_err());
+        assert!(CronSchedule::parse("* * 0 * *").is_err());
+        assert!(CronSchedule::parse("*/0 * * * *").is_err());
+        assert!(CronSchedule::parse("5-1 * * * *").is_err());
+        assert!(CronSchedule::parse("* * * FOO *").is_err());
+        assert!(CronSchedule::daily(24, 0, 0).is_err());
+        assert!(CronSchedule::weekly(8, 0, 0, 0).is_err());
+    }
+
+    #[test]
+    fn test_cron_next_after() {
+        // every day at 05:00, UTC+8
+        let daily = CronSchedule::daily(5, 0, 0).unwrap();
+        let now = local_ms(2026, 10, 17, 12, 30, 0);
+        assert_eq!(
+            daily.next_after(now, TZ8),
+            Some(local_ms(2026, 10, 18, 5, 0, 0))
+        );
+        // strictly after
+        let at = local_ms(2026, 10, 18, 5, 0, 0);
+        assert_eq!(
+            daily.next_after(at, TZ8),
+            Some(local_ms(2026, 10, 19, 5, 0, 0))
+        );
+        assert_eq!(daily.next_after(at - 1, TZ8), Some(at));
+
+        // every monday 00:00
+        let weekly = CronSchedule::weekly(1, 0, 0, 0).unwrap();
+        assert_eq!(
+            weekly.next_after(now, TZ8),
+            Some(local_ms(2026, 10, 19, 0, 0, 0))
+        );
+        assert_eq!(
+            weekly.next_after(now, 0),
+            Some(local_ms(2026, 10, 19, 8, 0, 0))
+        );
+
+        // steps and lists, year wrap
+        let cron = CronSchedule::parse("30 */20 9,18 * * *").unwrap();
+        assert_eq!(
+            cron.next_after(local_ms(2026, 12, 31, 18, 40, 30), TZ8),
+            Some(local_ms(2027, 1, 1, 9, 0, 30))
+        );
+
+        // day of month or weekday
+        let cron = CronSchedule::parse("0 0 13 * 5").unwrap();
+        assert_eq!(
+            cron.next_after(local_ms(2026, 10, 17, 0, 0, 0), TZ8),
+            Some(local_ms(2026, 10, 23, 0, 0, 0))
+        );
+        assert_eq!(
+            cron.next_after(local_ms(2026, 11, 6, 0, 0, 0), TZ8),
+            Some(local_ms(2026, 11, 13, 0, 0, 0))
+        );
+
+        // leap day, and a day that never comes
+        let cron = CronSchedule::parse("0 0 29 2 *").unwrap();
+        assert_eq!(
+            cron.next_after(now, TZ8),
+            Some(local_ms(2028, 2, 29, 0, 0, 0))
+        );
+        let cron = CronSchedule::parse("0 0 30 2 *").unwrap();
+        assert_eq!(cron.next_after(now, TZ8), None);
+    }
+}
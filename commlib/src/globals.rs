@@ -1,10 +1,71 @@
+use std::sync::atomic::{AtomicBool, Ordering};
 use std::sync::Arc;
 
+use crate::utils::ThreadPoolBuilder;
+
+/// G_THREAD_POOL 默认的线程数
+pub const DEFAULT_THREAD_POOL_SIZE: usize = 4;
+
+// init_thread_pool 设置的配置，第一次使用 G_THREAD_POOL 时取出
+static G_THREAD_POOL_CONFIG: parking_lot::Mutex<Option<ThreadPoolBuilder>> =
+    parking_lot::const_mutex(None);
+static G_THREAD_POOL_STARTED: AtomicBool = AtomicBool::new(false);
+
 lazy_static::lazy_static! {
-    ///
+    /// 全局线程池，默认 [`DEFAULT_THREAD_POOL_SIZE`] 个线程，可以在第一次使用前用 [`init_thread_pool`] 配置
     pub static ref G_THREAD_POOL: Arc<crate::utils::ThreadPool> = {
-        //
-        let pool = crate::utils::ThreadPoolBuilder::new().num_threads(4).build();
+        let builder = {
+            let mut config = G_THREAD_POOL_CONFIG.lock();
+            G_THREAD_POOL_STARTED.store(true, Ordering::SeqCst);
+            config.take()
+        };
+        let pool = builder
+            .unwrap_or_else(|| ThreadPoolBuilder::new().num_threads(DEFAULT_THREAD_POOL_SIZE))
+            .build();
         Arc::new(pool)
     };
 }
+
+/// 按配置创建 G_THREAD_POOL（比如从配置文件读取线程数），必须在第一次使用 G_THREAD_POOL 之前调用
+///
+/// 关服时用 `G_THREAD_POOL.shutdown(..)` 等待未完成的任务。
+pub fn init_thread_pool(builder: ThreadPoolBuilder) -> Result<(), String> {
+    {
+        let mut config = G_THREAD_POOL_CONFIG.lock();
+        // 只有配置被采用的调用者返回 Ok
+        if G_THREAD_POOL_STARTED.swap(true, Ordering::SeqCst) {
+            return Err("G_THREAD_POOL is already initialized".to_owned());
+        }
+        *config = Some(builder);
+    }
+    lazy_static::initialize(&G_THREAD_POOL);
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{init_thread_pool, G_THREAD_POOL};
+    use crate::utils::ThreadPoolBuilder;
+
+    #[test]
+    fn test_init_thread_pool() {
+        // concurrent callers: only the one whose builder is used gets Ok
+        let handles: Vec<_> = (2..6)
+            .map(|n| {
+                std::thread::spawn(move || {
+                    init_thread_pool(ThreadPoolBuilder::new().num_threads(n))
+                        .ok()
+                        .map(|_| n)
+                })
+            })
+            .collect();
+        let used: Vec<_> = handles
+            .into_iter()
+            .filter_map(|h| h.join().unwrap())
+            .collect();
+        assert_eq!(used.len(), 1);
+        assert_eq!(G_THREAD_POOL.max_count(), used[0]);
+        assert!(init_thread_pool(ThreadPoolBuilder::new().num_threads(8)).is_err());
+        assert_eq!(G_THREAD_POOL.submit(1, || 42).join().unwrap(), 42);
+    }
+}
@@ -73,8 +73,110 @@ pub enum TimerError<EntryType> {
     NotFound,
     /// The timout has already expired
     Expired(EntryType),
+    /// A periodic timeout was scheduled with a 0ms period
+    ZeroPeriod(EntryType),
+    /// The work queue of the timer thread was disconnected
+    Disconnected,
 }
 
+/// What to do with the timer that caused a [TimerError]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum TimerErrorAction {
+    /// Drop the timer
+    Drop,
+    /// Reschedule the timer with a 1ms period
+    Clamp,
+}
+
+/// A custom [TimerErrorHook]
+pub type TimerErrorFn<I> = std::sync::Arc<dyn Fn(&TimerError<I>) -> TimerErrorAction + Send + Sync>;
+
+/// Reports [TimerError]s of a timer implementation instead of panicking
+///
+/// Errors are reported with the id of the timer.
+#[derive(Default)]
+pub enum TimerErrorHook<I> {
+    /// Log the error and drop the timer
+    #[default]
+    Log,
+    /// Silently drop the timer
+    Drop,
+    /// Log a warning and clamp a 0ms period to 1ms
+    Clamp,
+    /// Custom hook deciding what to do with the timer
+    Custom(TimerErrorFn<I>),
+}
+
+impl<I> TimerErrorHook<I>
+where
+    I: std::fmt::Debug,
+{
+    /// Create a custom hook
+    pub fn custom<F>(f: F) -> Self
+    where
+        F: Fn(&TimerError<I>) -> TimerErrorAction + Send + Sync + 'static,
+    {
+        TimerErrorHook::Custom(std::sync::Arc::new(f))
+    }
+
+    /// Report the error, returning what to do with the timer
+    pub fn report(&self, err: &TimerError<I>) -> TimerErrorAction {
+        match self {
+            TimerErrorHook::Log => {
+                log::error!("timer error: {:?}, timer is dropped", err);
+                TimerErrorAction::Drop
+            }
+            TimerErrorHook::Drop => TimerErrorAction::Drop,
+            TimerErrorHook::Clamp => {
+                log::warn!("timer error: {:?}, period is clamped to 1ms", err);
+                TimerErrorAction::Clamp
+            }
+            TimerErrorHook::Custom(f) => f(err),
+        }
+    }
+
+    /// Check the period of a periodic timer before it's scheduled
+    ///
+    /// A 0ms period is reported, `None` means the timer must not be scheduled.
+    pub fn checked_period(&self, id: &I, period: Duration) -> Option<Duration>
+    where
+        I: Clone,
+    {
+        if period.as_millis() > 0 {
+            return Some(period);
+        }
+        match self.report(&TimerError::ZeroPeriod(id.clone())) {
+            TimerErrorAction::Drop => None,
+            TimerErrorAction::Clamp => Some(CLAMPED_PERIOD),
+        }
+    }
+}
+
+impl<I> Clone for TimerErrorHook<I> {
+    fn clone(&self) -> Self {
+        match self {
+            TimerErrorHook::Log => TimerErrorHook::Log,
+            TimerErrorHook::Drop => TimerErrorHook::Drop,
+            TimerErrorHook::Clamp => TimerErrorHook::Clamp,
+            TimerErrorHook::Custom(f) => TimerErrorHook::Custom(f.clone()),
+        }
+    }
+}
+
+impl<I> std::fmt::Debug for TimerErrorHook<I> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            TimerErrorHook::Log => write!(f, "TimerErrorHook::Log"),
+            TimerErrorHook::Drop => write!(f, "TimerErrorHook::Drop"),
+            TimerErrorHook::Clamp => write!(f, "TimerErrorHook::Clamp"),
+            TimerErrorHook::Custom(_) => write!(f, "TimerErrorHook::Custom(<function>)"),
+        }
+    }
+}
+
+/// The period used for a 0ms period clamped by [TimerErrorAction::Clamp]
+pub const CLAMPED_PERIOD: Duration = Duration::from_millis(1);
+
 /// A simple implementation of a timer entry that only stores its own unique id and the original delay
 #[derive(Debug)]
 pub struct IdOnlyTimerEntry<I> {
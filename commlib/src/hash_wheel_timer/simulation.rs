@@ -11,6 +11,7 @@
 use super::wheels::{cancellable::*, *};
 use super::*;
 use std::{
+    convert::Infallible,
     fmt::Debug,
     hash::Hash,
     time::{Duration, SystemTime},
@@ -92,6 +93,7 @@ where
 {
     time: u128,
     timer: QuadWheelWithOverflow<SimulationEntry<I, O, P>>,
+    error_hook: TimerErrorHook<I>,
 }
 
 impl<I, O, P> SimulationTimer<I, O, P>
@@ -105,6 +107,7 @@ where
         SimulationTimer {
             time: 0u128,
             timer: QuadWheelWithOverflow::new(),
+            error_hook: TimerErrorHook::default(),
         }
     }
 
@@ -117,6 +120,7 @@ where
         SimulationTimer {
             time: tms,
             timer: QuadWheelWithOverflow::new(),
+            error_hook: TimerErrorHook::default(),
         }
     }
 
@@ -125,6 +129,11 @@ where
         self.time
     }
 
+    /// Set the hook reporting timer errors, such as a 0ms period
+    pub fn set_error_hook(&mut self, hook: TimerErrorHook<I>) {
+        self.error_hook = hook;
+    }
+
     /// Advance the virtual time
     #[allow(clippy::should_implement_trait)]
     pub fn next(&mut self) -> SimulationStep {
@@ -157,18 +166,78 @@ where
         }
     }
 
+    /// Time left until the timer with the given `id` fires
+    ///
+    /// For a paused timer this is the delay left when it was paused.
+    pub fn remaining(&self, id: &I) -> Option<Duration> {
+        self.timer.remaining(id)
+    }
+
+    /// Number of pending timers, including paused ones
+    pub fn len(&self) -> usize {
+        self.timer.len()
+    }
+
+    /// Whether there are no pending timers
+    pub fn is_empty(&self) -> bool {
+        self.timer.is_empty()
+    }
+
+    /// Move the timer with the given `id` to fire after `delay` from now
+    ///
+    /// A zero `delay` fires the timer right away. A paused timer stays paused.
+    pub fn reschedule(&mut self, id: &I, delay: Duration) -> Result<(), TimerError<Infallible>> {
+        match self.timer.reschedule(id, delay) {
+            Ok(_) => Ok(()),
+            Err(TimerError::Expired(e)) => {
+                self.trigger_entry(e);
+                Ok(())
+            }
+            Err(_) => Err(TimerError::NotFound),
+        }
+    }
+
+    /// Stop the clock for the timer with the given `id` until it's resumed
+    ///
+    /// Paused timers don't keep the simulation going.
+    pub fn pause(&mut self, id: &I) -> Result<(), TimerError<Infallible>> {
+        self.timer.pause(id)
+    }
+
+    /// Restart the clock for a paused timer with the given `id`
+    pub fn resume(&mut self, id: &I) -> Result<(), TimerError<Infallible>> {
+        self.timer.resume(id)
+    }
+
     fn trigger_entry(&mut self, e: std::sync::Arc<SimulationEntry<I, O, P>>) {
         if let Some((new_e, delay)) = SimulationEntry::execute_unique_ref(e) {
-            match self.timer.insert_ref_with_delay(new_e, delay) {
-                Ok(_) => (), // ok
-                Err(TimerError::Expired(e)) => panic!(
-                    "Trying to insert periodic timer entry with 0ms period! {:?}",
-                    e
-                ),
-                Err(f) => panic!("Could not insert timer entry! {:?}", f),
-            }
+            self.reschedule_periodic(new_e, delay);
         } // otherwise, timer is not rescheduled
     }
+
+    // Reschedule a periodic entry, a 0ms period goes to the error hook
+    fn reschedule_periodic(
+        &mut self,
+        e: std::sync::Arc<SimulationEntry<I, O, P>>,
+        period: Duration,
+    ) {
+        match self.timer.insert_ref_with_delay(e, period) {
+            Ok(_) => (), // ok
+            Err(TimerError::Expired(mut e)) => {
+                let err = TimerError::ZeroPeriod(e.id().clone());
+                if self.error_hook.report(&err) == TimerErrorAction::Clamp {
+                    if let Some(SimulationEntry::Periodic { period, .. }) =
+                        std::sync::Arc::get_mut(&mut e)
+                    {
+                        *period = CLAMPED_PERIOD;
+                    }
+                    let _ = self.timer.insert_ref_with_delay(e, CLAMPED_PERIOD);
+                    // 1ms never expires
+                }
+            }
+            Err(f) => panic!("Could not insert timer entry! {:?}", f),
+        }
+    }
 }
 
 impl<I, O, P> Default for SimulationTimer<I, O, P>
@@ -244,24 +313,17 @@ where
     }
 
     fn schedule_periodic(&mut self, delay: Duration, period: Duration, state: Self::PeriodicState) {
+        let period = match self.error_hook.checked_period(state.id(), period) {
+            Some(period) => period,
+            None => return, // rejected
+        };
         let e = SimulationEntry::Periodic { period, state };
         match self
             .timer
             .insert_ref_with_delay(std::sync::Arc::new(e), delay)
         {
             Ok(_) => (), // ok
-            Err(TimerError::Expired(e)) => {
-                if let Some((new_e, delay)) = SimulationEntry::execute_unique_ref(e) {
-                    match self.timer.insert_ref_with_delay(new_e, delay) {
-                        Ok(_) => (), // ok
-                        Err(TimerError::Expired(e)) => panic!(
-                            "Trying to insert periodic timer entry with 0ms period! {:?}",
-                            e
-                        ),
-                        Err(f) => panic!("Could not insert timer entry! {:?}", f),
-                    }
-                }
-            } // otherwise, timer decided not to reschedule itself
+            Err(TimerError::Expired(e)) => self.trigger_entry(e),
             Err(f) => panic!("Could not insert timer entry! {:?}", f),
         }
     }
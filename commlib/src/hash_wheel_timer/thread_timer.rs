@@ -14,7 +14,7 @@ use std::{cmp::Ordering, fmt, hash::Hash, io, thread, time::Duration, time::Inst
 use super::wheels::{cancellable::QuadWheelWithOverflow, Skip};
 use super::{
     CancellableTimerEntry, OneShotClosureState, OneshotState, PeriodicClosureState, PeriodicState,
-    Timer, TimerEntry, TimerError, TimerReturn,
+    Timer, TimerEntry, TimerError, TimerErrorAction, TimerErrorHook, TimerReturn, CLAMPED_PERIOD,
 };
 
 #[derive(Debug)]
@@ -42,11 +42,12 @@ where
     P: PeriodicState<Id = I> + Send + Sync,
 {
     work_queue: channel::Sender<TimerMsg<I, O, P>>,
+    error_hook: TimerErrorHook<I>,
 }
 
 impl<I, O, P> Timer for TimerRef<I, O, P>
 where
-    I: Hash + Clone + Eq + Send + Sync,
+    I: Hash + Clone + Eq + fmt::Debug + Send + Sync,
     O: OneshotState<Id = I> + Send + Sync,
     P: PeriodicState<Id = I> + Send + Sync,
 {
@@ -62,6 +63,10 @@ where
     }
 
     fn schedule_periodic(&mut self, delay: Duration, period: Duration, state: Self::PeriodicState) {
+        let period = match self.error_hook.checked_period(state.id(), period) {
+            Some(period) => period,
+            None => return, // rejected
+        };
         let e = TimerEntry::Periodic {
             delay,
             period,
@@ -90,6 +95,7 @@ where
 {
     timer_thread: thread::JoinHandle<()>,
     work_queue: channel::Sender<TimerMsg<I, O, P>>,
+    error_hook: TimerErrorHook<I>,
 }
 
 impl<I, O, P> TimerWithThread<I, O, P>
@@ -102,16 +108,25 @@ where
     ///
     /// The thread will be called `"timer-thread"`.
     pub fn new() -> io::Result<TimerWithThread<I, O, P>> {
+        Self::with_error_hook(TimerErrorHook::default())
+    }
+
+    /// Create a new timer with its own thread, reporting timer errors to `error_hook`
+    ///
+    /// The hook is shared by the thread and all [TimerRef]s of this timer.
+    pub fn with_error_hook(error_hook: TimerErrorHook<I>) -> io::Result<TimerWithThread<I, O, P>> {
         let (s, r) = channel::unbounded();
+        let thread_hook = error_hook.clone();
         let handle = thread::Builder::new()
             .name("timer-thread".to_string())
             .spawn(move || {
-                let timer = TimerThread::new(r);
+                let timer = TimerThread::new(r, thread_hook);
                 timer.run();
             })?;
         let twt = TimerWithThread {
             timer_thread: handle,
             work_queue: s,
+            error_hook,
         };
         Ok(twt)
     }
@@ -123,6 +138,7 @@ where
     pub fn timer_ref(&self) -> TimerRef<I, O, P> {
         TimerRef {
             work_queue: self.work_queue.clone(),
+            error_hook: self.error_hook.clone(),
         }
     }
 
@@ -284,6 +300,7 @@ where
     running: bool,
     start: Instant,
     last_check: u128,
+    error_hook: TimerErrorHook<I>,
 }
 
 impl<I, O, P> TimerThread<I, O, P>
@@ -292,13 +309,17 @@ where
     O: OneshotState<Id = I> + fmt::Debug + Send + Sync,
     P: PeriodicState<Id = I> + fmt::Debug + Send + Sync,
 {
-    fn new(work_queue: channel::Receiver<TimerMsg<I, O, P>>) -> TimerThread<I, O, P> {
+    fn new(
+        work_queue: channel::Receiver<TimerMsg<I, O, P>>,
+        error_hook: TimerErrorHook<I>,
+    ) -> TimerThread<I, O, P> {
         TimerThread {
             timer: QuadWheelWithOverflow::new(),
             work_queue,
             running: true,
             start: Instant::now(),
             last_check: 0u128,
+            error_hook,
         }
     }
 
@@ -327,9 +348,7 @@ where
                                     self.reset(); // since we waited for an arbitrary time and taking a new timestamp incurs no error
                                     self.handle_msg(msg)
                                 }
-                                Err(channel::RecvError) => {
-                                    panic!("Timer work_queue unexpectedly shut down!")
-                                }
+                                Err(channel::RecvError) => self.disconnected(),
                             }
                         }
                         Skip::Millis(can_skip) if can_skip > 5 => {
@@ -353,13 +372,17 @@ where
                         }
                     }
                 }
-                Err(channel::TryRecvError::Disconnected) => {
-                    panic!("Timer work_queue unexpectedly shut down!")
-                }
+                Err(channel::TryRecvError::Disconnected) => self.disconnected(),
             }
         }
     }
 
+    // All senders are gone without a Stop msg, nothing can be scheduled anymore
+    fn disconnected(&mut self) {
+        self.error_hook.report(&TimerError::Disconnected);
+        self.running = false;
+    }
+
     #[inline(always)]
     fn skip_and_tick(&mut self, can_skip: u32, elapsed: u128) {
         let can_skip_u128 = can_skip as u128;
@@ -427,10 +450,18 @@ where
         if let Some((new_e, delay)) = ThreadTimerEntry::execute_unique_ref(e) {
             match self.timer.insert_ref_with_delay(new_e, delay) {
                 Ok(_) => (), // ok
-                Err(TimerError::Expired(e)) => panic!(
-                    "Trying to insert periodic timer entry with 0ms period! {:?}",
-                    e
-                ),
+                Err(TimerError::Expired(mut e)) => {
+                    let err = TimerError::ZeroPeriod(e.id().clone());
+                    if self.error_hook.report(&err) == TimerErrorAction::Clamp {
+                        if let Some(ThreadTimerEntry::Periodic { period, .. }) =
+                            std::sync::Arc::get_mut(&mut e)
+                        {
+                            *period = CLAMPED_PERIOD;
+                        }
+                        let _ = self.timer.insert_ref_with_delay(e, CLAMPED_PERIOD);
+                        // 1ms never expires
+                    }
+                }
                 Err(f) => panic!("Could not insert timer entry! {:?}", f),
             }
         } // otherwise: timer is not rescheduled
@@ -569,4 +600,38 @@ mod tests {
             assert!(*guard);
         }
     }
+
+    #[test]
+    fn disconnected_work_queue() {
+        let (tx, rx) = std::sync::mpsc::channel();
+        let tx = std::sync::Mutex::new(tx);
+        let timer_core: TimerWithThread<u64, _, _> =
+            TimerWithThread::with_error_hook(TimerErrorHook::custom(move |e| {
+                let _ = tx.lock().unwrap().send(format!("{:?}", e));
+                TimerErrorAction::Drop
+            }))
+            .expect("timer");
+        let mut timer = timer_core.timer_ref();
+        timer.schedule_action_periodic(1, Duration::from_millis(1), Duration::ZERO, |_: u64| {
+            TimerReturn::Reschedule(())
+        });
+        assert_eq!(
+            rx.recv_timeout(Duration::from_secs(1)).as_deref(),
+            Ok("ZeroPeriod(1)")
+        );
+
+        // all senders gone without a Stop msg, the thread stops instead of panicking
+        let TimerWithThread {
+            timer_thread,
+            work_queue,
+            ..
+        } = timer_core;
+        drop(work_queue);
+        drop(timer);
+        assert_eq!(
+            rx.recv_timeout(Duration::from_secs(1)).as_deref(),
+            Ok("Disconnected")
+        );
+        assert!(timer_thread.join().is_ok());
+    }
 }
@@ -7,16 +7,18 @@
 //!
 
 use std::{
+    collections::VecDeque,
+    convert::Infallible,
     fmt::Debug,
     hash::Hash,
     sync::Arc,
-    time::{Duration, SystemTime},
+    time::{Duration, Instant, SystemTime},
 };
 
 use super::wheels::{cancellable::QuadWheelWithOverflow, Skip};
 use super::{
     CancellableTimerEntry, OneShotClosureState, OneshotState, PeriodicClosureState, PeriodicState,
-    Timer, TimerError, TimerReturn,
+    Timer, TimerError, TimerErrorAction, TimerErrorHook, TimerReturn, CLAMPED_PERIOD,
 };
 
 pub use super::timers::TimerEntry;
@@ -77,6 +79,62 @@ where
     }
 }
 
+type ExpiredEntry<I, O, P> = Arc<TimerEntry<I, O, P>>;
+
+/// Limits on the work done by a single [update](WheelTimer::update)
+///
+/// Expired timers over the budget wait for the next update, in the order they expired.
+/// Every update triggers at least one waiting timer, so the backlog always drains.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct UpdateBudget {
+    /// Max number of timer actions triggered per update
+    pub max_callbacks: Option<usize>,
+    /// Max time spent in timer actions per update
+    pub max_duration: Option<Duration>,
+}
+
+impl UpdateBudget {
+    /// No limits, every expired timer is triggered in the same update
+    pub const UNLIMITED: Self = Self {
+        max_callbacks: None,
+        max_duration: None,
+    };
+
+    /// At most `n` timer actions per update
+    pub fn callbacks(n: usize) -> Self {
+        Self {
+            max_callbacks: Some(n),
+            max_duration: None,
+        }
+    }
+
+    /// Stop triggering timer actions once `d` has been spent in an update
+    pub fn duration(d: Duration) -> Self {
+        Self {
+            max_callbacks: None,
+            max_duration: Some(d),
+        }
+    }
+
+    fn exhausted(&self, fired: usize, start: Instant) -> bool {
+        self.max_callbacks.is_some_and(|n| fired >= n)
+            || self.max_duration.is_some_and(|d| start.elapsed() >= d)
+    }
+}
+
+/// What a periodic timer does about occurrences missed during a long update
+/// (or while waiting on the [budget](UpdateBudget))
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum CatchUpPolicy {
+    /// Trigger every missed occurrence, keeping the original phase
+    FireAll,
+    /// Trigger once, the next occurrence is a full period after that
+    #[default]
+    FireOnce,
+    /// Trigger once, drop the missed occurrences and keep the original phase
+    SkipMissed,
+}
+
 /// A timer implementation that used timing-wheel
 pub struct WheelTimer<I, O, P>
 where
@@ -86,6 +144,17 @@ where
 {
     time: u128,
     timer: QuadWheelWithOverflow<TimerEntry<I, O, P>>,
+
+    // ids of expired entries which are waiting or being triggered
+    firing: hashbrown::HashSet<I>,
+
+    // expired entries over the budget, with the time they expired at
+    overdue: VecDeque<(u128, ExpiredEntry<I, O, P>)>,
+
+    budget: UpdateBudget,
+    catch_up: CatchUpPolicy,
+
+    error_hook: TimerErrorHook<I>,
 }
 
 impl<I, O, P> WheelTimer<I, O, P>
@@ -99,6 +168,11 @@ where
         WheelTimer {
             time: 0u128,
             timer: QuadWheelWithOverflow::new(),
+            firing: hashbrown::HashSet::new(),
+            overdue: VecDeque::new(),
+            budget: UpdateBudget::UNLIMITED,
+            catch_up: CatchUpPolicy::default(),
+            error_hook: TimerErrorHook::default(),
         }
     }
 
@@ -111,6 +185,11 @@ where
         WheelTimer {
             time: tms,
             timer: QuadWheelWithOverflow::new(),
+            firing: hashbrown::HashSet::new(),
+            overdue: VecDeque::new(),
+            budget: UpdateBudget::UNLIMITED,
+            catch_up: CatchUpPolicy::default(),
+            error_hook: TimerErrorHook::default(),
         }
     }
 
@@ -119,24 +198,164 @@ where
         self.time
     }
 
+    /// Set the hook reporting timer errors, such as a 0ms period
+    pub fn set_error_hook(&mut self, hook: TimerErrorHook<I>) {
+        self.error_hook = hook;
+    }
+
+    /// Set the limits on the timer actions triggered by a single update
+    pub fn set_update_budget(&mut self, budget: UpdateBudget) {
+        self.budget = budget;
+    }
+
+    /// Set how periodic timers catch up on missed occurrences
+    pub fn set_catch_up(&mut self, policy: CatchUpPolicy) {
+        self.catch_up = policy;
+    }
+
+    /// Number of expired timers waiting for the next update because of the budget
+    pub fn backlog(&self) -> usize {
+        self.overdue
+            .iter()
+            .filter(|(_, e)| self.firing.contains(e.id()))
+            .count()
+    }
+
     /// Update by ms
+    ///
+    /// Timers left over by the previous update are triggered first, then the ones expiring in `d`.
     #[inline(always)]
     pub fn update(&mut self, d: std::time::Duration) {
-        let expired_vec = self.collect_expired(d);
-        let to_reschedule_vec = Self::trigger_expired(expired_vec);
-        self.reschedule(to_reschedule_vec);
+        self.collect_expired(d);
+        self.trigger_expired();
+    }
+
+    /// Cancel the timer with the given `id`, reporting whether it was found
+    ///
+    /// A periodic timer cancelled from inside its own action is not rescheduled.
+    pub fn try_cancel(&mut self, id: &I) -> Result<(), TimerError<Infallible>> {
+        match self.timer.cancel(id) {
+            Ok(_) => Ok(()),
+            Err(TimerError::NotFound) => {
+                // the entry is taken out of the wheel while triggering
+                if self.firing.remove(id) {
+                    Ok(())
+                } else {
+                    Err(TimerError::NotFound)
+                }
+            }
+            Err(f) => Err(f),
+        }
+    }
+
+    /// Time left until the timer with the given `id` fires
+    ///
+    /// For a paused timer this is the delay left when it was paused.
+    pub fn remaining(&self, id: &I) -> Option<Duration> {
+        match self.timer.remaining(id) {
+            Some(left) => Some(left),
+            None if self.is_overdue(id) => Some(Duration::ZERO),
+            None => None,
+        }
+    }
+
+    /// Number of pending timers, including paused ones and the backlog
+    pub fn len(&self) -> usize {
+        self.timer.len() + self.backlog()
+    }
+
+    /// Whether there are no pending timers
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// Move the timer with the given `id` to fire after `delay` from now
+    ///
+    /// A zero `delay` fires the timer right away. A paused timer stays paused.
+    pub fn reschedule(&mut self, id: &I, delay: Duration) -> Result<(), TimerError<Infallible>> {
+        if let Some(e) = self.take_overdue(id) {
+            return match self.timer.insert_ref_with_delay(e, delay) {
+                Ok(_) => Ok(()),
+                Err(TimerError::Expired(e)) => {
+                    self.trigger_now(e);
+                    Ok(())
+                }
+                Err(_) => Err(TimerError::NotFound),
+            };
+        }
+        match self.timer.reschedule(id, delay) {
+            Ok(_) => Ok(()),
+            Err(TimerError::Expired(e)) => {
+                self.trigger_now(e);
+                Ok(())
+            }
+            Err(_) => Err(TimerError::NotFound),
+        }
+    }
+
+    /// Stop the clock for the timer with the given `id` until it's resumed
+    pub fn pause(&mut self, id: &I) -> Result<(), TimerError<Infallible>> {
+        if let Some(e) = self.take_overdue(id) {
+            // back into the wheel first, it fires right after being resumed
+            let _ = self.timer.insert_ref_with_delay(e, CLAMPED_PERIOD);
+        }
+        self.timer.pause(id)
+    }
+
+    /// Restart the clock for a paused timer with the given `id`
+    pub fn resume(&mut self, id: &I) -> Result<(), TimerError<Infallible>> {
+        self.timer.resume(id)
+    }
+
+    // Trigger an entry whose delay is already over, and reschedule it if it's periodic
+    fn trigger_now(&mut self, e: Arc<TimerEntry<I, O, P>>) {
+        if let Some((new_e, period)) = TimerEntry::execute_unique_ref(e) {
+            self.reschedule_periodic(new_e, period);
+        } // otherwise, timer decided not to reschedule itself
+    }
+
+    // Reschedule a periodic entry, a 0ms period goes to the error hook
+    fn reschedule_periodic(&mut self, e: Arc<TimerEntry<I, O, P>>, period: Duration) {
+        match self.timer.insert_ref_with_delay(e, period) {
+            Ok(_) => (), // ok
+            Err(TimerError::Expired(mut e)) => {
+                let err = TimerError::ZeroPeriod(e.id().clone());
+                if self.error_hook.report(&err) == TimerErrorAction::Clamp {
+                    if let Some(TimerEntry::Periodic { period, .. }) = Arc::get_mut(&mut e) {
+                        *period = CLAMPED_PERIOD;
+                    }
+                    let _ = self.timer.insert_ref_with_delay(e, CLAMPED_PERIOD);
+                    // 1ms never expires
+                }
+            }
+            Err(f) => panic!("Could not insert timer entry! {:?}", f),
+        }
+    }
+
+    fn is_overdue(&self, id: &I) -> bool {
+        self.firing.contains(id) && self.overdue.iter().any(|(_, e)| e.id() == id)
+    }
+
+    // Take an entry waiting on the budget out of the backlog
+    fn take_overdue(&mut self, id: &I) -> Option<Arc<TimerEntry<I, O, P>>> {
+        if !self.firing.contains(id) {
+            return None;
+        }
+        let pos = self.overdue.iter().position(|(_, e)| e.id() == id)?;
+        self.firing.remove(id);
+        self.overdue.remove(pos).map(|(_, e)| e)
     }
 
     // Update: collect expired
     #[inline(always)]
-    fn collect_expired(&mut self, d: std::time::Duration) -> Vec<Arc<TimerEntry<I, O, P>>> {
-        let mut expired_vec: Vec<Arc<TimerEntry<I, O, P>>> = Vec::with_capacity(256);
-
+    fn collect_expired(&mut self, d: std::time::Duration) {
         let mut delta = d.as_millis() as u32;
         while delta > 0 {
             match self.timer.can_skip() {
                 Skip::Empty => {
-                    // Wheel is empty, or , do nothing
+                    // Wheel is empty, time still goes on for the catch-up of the expired entries
+                    self.timer.skip(delta);
+                    self.time += delta as u128;
                     break;
                 }
                 Skip::None => {
@@ -148,7 +367,8 @@ where
 
                     // collect
                     for e in res {
-                        expired_vec.push(e);
+                        self.firing.insert(e.id().clone());
+                        self.overdue.push_back((self.time, e));
                     }
                 }
                 Skip::Millis(ms) => {
@@ -161,38 +381,65 @@ where
                 }
             }
         }
-
-        //
-        expired_vec
     }
 
-    // Update: trigger expired
+    // Update: trigger expired within the budget, the rest waits for the next update
     #[inline(always)]
-    fn trigger_expired(
-        expired_vec: Vec<Arc<TimerEntry<I, O, P>>>,
-    ) -> Vec<(Arc<TimerEntry<I, O, P>>, Duration)> {
-        let mut to_reschedule_vec = Vec::with_capacity(expired_vec.len());
-        for e in expired_vec {
-            if let Some(pair) = TimerEntry::execute_unique_ref(e) {
-                to_reschedule_vec.push(pair);
+    fn trigger_expired(&mut self) {
+        let start = Instant::now();
+        let mut fired = 0usize;
+        while let Some((due, e)) = self.overdue.pop_front() {
+            if !self.firing.contains(e.id()) {
+                // cancelled while waiting
+                continue;
+            }
+            if fired > 0 && self.budget.exhausted(fired, start) {
+                self.overdue.push_front((due, e));
+                break;
+            }
+            fired += 1;
+
+            let id = e.id().clone();
+            let requeued = match TimerEntry::execute_unique_ref(e) {
+                // not cancelled by its own action
+                Some((new_e, period)) if self.firing.contains(&id) => {
+                    self.catch_up(due, new_e, period)
+                }
+                _ => false, // timer is not rescheduled
+            };
+            if !requeued {
+                self.firing.remove(&id);
             }
         }
-        to_reschedule_vec
     }
 
-    // Update: reschedule
-    #[inline(always)]
-    fn reschedule(&mut self, to_reschedule_vec: Vec<(Arc<TimerEntry<I, O, P>>, Duration)>) {
-        for (new_e, delay) in to_reschedule_vec {
-            match self.timer.insert_ref_with_delay(new_e, delay) {
-                Ok(_) => (), // ok
-                Err(TimerError::Expired(e)) => panic!(
-                    "Trying to insert periodic timer entry with 0ms period! {:?}",
-                    e
-                ),
-                Err(f) => panic!("Could not insert timer entry! {:?}", f),
+    // Update: reschedule a periodic entry which was due at `due`, returns whether
+    // it's back in the backlog for a missed occurrence
+    fn catch_up(&mut self, due: u128, e: Arc<TimerEntry<I, O, P>>, period: Duration) -> bool {
+        let period_ms = period.as_millis();
+        let next = match self.catch_up {
+            _ if period_ms == 0 => {
+                self.reschedule_periodic(e, period);
+                return false;
+            }
+            CatchUpPolicy::FireOnce => {
+                self.reschedule_periodic(e, period);
+                return false;
             }
-        } // otherwise, timer is not rescheduled
+            CatchUpPolicy::FireAll => due + period_ms,
+            CatchUpPolicy::SkipMissed => due + ((self.time - due) / period_ms + 1) * period_ms,
+        };
+
+        if next <= self.time {
+            // keep the backlog ordered by expiry
+            let pos = self.overdue.partition_point(|(t, _)| *t <= next);
+            self.overdue.insert(pos, (next, e));
+            true
+        } else {
+            let delay = Duration::from_millis((next - self.time) as u64);
+            self.reschedule_periodic(e, delay);
+            false
+        }
     }
 }
 
@@ -264,6 +511,10 @@ where
     }
 
     fn schedule_periodic(&mut self, delay: Duration, period: Duration, state: Self::PeriodicState) {
+        let period = match self.error_hook.checked_period(state.id(), period) {
+            Some(period) => period,
+            None => return, // rejected
+        };
         let e = TimerEntry::Periodic {
             delay,
             period,
@@ -271,24 +522,13 @@ where
         };
         match self.timer.insert_ref_with_delay(Arc::new(e), delay) {
             Ok(_) => (), // ok
-            Err(TimerError::Expired(e)) => {
-                if let Some((new_e, delay)) = TimerEntry::execute_unique_ref(e) {
-                    match self.timer.insert_ref_with_delay(new_e, delay) {
-                        Ok(_) => (), // ok
-                        Err(TimerError::Expired(e)) => panic!(
-                            "Trying to insert periodic timer entry with 0ms period! {:?}",
-                            e
-                        ),
-                        Err(f) => panic!("Could not insert timer entry! {:?}", f),
-                    }
-                }
-            } // otherwise, timer decided not to reschedule itself
+            Err(TimerError::Expired(e)) => self.trigger_now(e),
             Err(f) => panic!("Could not insert timer entry! {:?}", f),
         }
     }
 
     fn cancel(&mut self, id: &Self::Id) {
-        match self.timer.cancel(id) {
+        match self.try_cancel(id) {
             Ok(_) => (),                                                             // great
             Err(f) => eprintln!("Could not cancel timer with id={:?}. {:?}", id, f), // not so great, but meh
         }
@@ -298,9 +538,11 @@ where
 #[cfg(feature = "uuid-extras")]
 #[cfg(test)]
 mod tests {
+    use crate::hash_wheel_timer::simulation::{SimulationStep, SimulationTimer};
     use crate::hash_wheel_timer::test_helpers::*;
     use crate::hash_wheel_timer::timers::ClosureTimer;
     use crate::hash_wheel_timer::wheel_timer::*;
+    use crate::hash_wheel_timer::TimerErrorAction;
     use parking_lot::Mutex;
     use uuid::Uuid;
     use Arc;
@@ -369,4 +611,212 @@ mod tests {
             assert!(*guard);
         }
     }
+
+    // schedule the same timers on both implementations
+    fn schedule_query_timers<T>(timer: &mut T, fired: &Arc<Mutex<Vec<u64>>>)
+    where
+        T: ClosureTimer<Id = u64>,
+    {
+        for (id, delay) in [(1u64, 10u64), (2, 20), (3, 30), (5, 40)] {
+            let fired = fired.clone();
+            timer.schedule_action_once(id, Duration::from_millis(delay), move |id| {
+                fired.lock().push(id);
+            });
+        }
+        let fired = fired.clone();
+        let mut counter = 2;
+        timer.schedule_action_periodic(
+            4u64,
+            Duration::from_millis(5),
+            Duration::from_millis(15),
+            move |id| {
+                fired.lock().push(id);
+                if counter > 0 {
+                    counter -= 1;
+                    TimerReturn::Reschedule(())
+                } else {
+                    TimerReturn::Cancel
+                }
+            },
+        );
+    }
+
+    fn drain_fired(fired: &Arc<Mutex<Vec<u64>>>, time: u128, log: &mut Vec<(u128, u64)>) {
+        let mut ids = std::mem::take(&mut *fired.lock());
+        ids.sort_unstable();
+        log.extend(ids.into_iter().map(|id| (time, id)));
+    }
+
+    #[test]
+    fn query_and_reschedule_like_simulation() {
+        let expected = vec![(5, 4), (20, 2), (20, 4), (23, 5), (25, 1), (35, 4), (50, 3)];
+
+        // WheelTimer
+        let fired = Arc::new(Mutex::new(Vec::new()));
+        let mut timer = WheelTimer::<u64, _, _>::for_closures();
+        schedule_query_timers(&mut timer, &fired);
+        assert_eq!(timer.len(), 5);
+        timer.reschedule(&1, Duration::from_millis(25)).unwrap();
+        timer.pause(&3).unwrap();
+        assert_eq!(timer.remaining(&1), Some(Duration::from_millis(25)));
+        assert_eq!(timer.remaining(&3), Some(Duration::from_millis(30)));
+        assert!(timer.reschedule(&6, Duration::from_millis(1)).is_err());
+
+        let mut wheel_log = vec![];
+        while timer.current_time() < 20 {
+            timer.update(Duration::from_millis(1));
+            drain_fired(&fired, timer.current_time(), &mut wheel_log);
+        }
+        assert_eq!(timer.remaining(&1), Some(Duration::from_millis(5)));
+        assert_eq!(timer.remaining(&3), Some(Duration::from_millis(30)));
+        assert_eq!(timer.remaining(&4), Some(Duration::from_millis(15)));
+        assert_eq!(timer.len(), 4);
+        timer.resume(&3).unwrap();
+        timer.reschedule(&5, Duration::from_millis(3)).unwrap();
+        while !timer.is_empty() && timer.current_time() < 1000 {
+            timer.update(Duration::from_millis(1));
+            drain_fired(&fired, timer.current_time(), &mut wheel_log);
+        }
+        assert_eq!(wheel_log, expected);
+
+        // SimulationTimer
+        let fired = Arc::new(Mutex::new(Vec::new()));
+        let mut timer = SimulationTimer::<u64, _, _>::for_closures();
+        schedule_query_timers(&mut timer, &fired);
+        assert_eq!(timer.len(), 5);
+        timer.reschedule(&1, Duration::from_millis(25)).unwrap();
+        timer.pause(&3).unwrap();
+        assert_eq!(timer.remaining(&1), Some(Duration::from_millis(25)));
+        assert_eq!(timer.remaining(&3), Some(Duration::from_millis(30)));
+        assert!(timer.reschedule(&6, Duration::from_millis(1)).is_err());
+
+        let mut sim_log = vec![];
+        while timer.current_time() < 20 {
+            timer.next();
+            drain_fired(&fired, timer.current_time(), &mut sim_log);
+        }
+        assert_eq!(timer.remaining(&1), Some(Duration::from_millis(5)));
+        assert_eq!(timer.remaining(&3), Some(Duration::from_millis(30)));
+        assert_eq!(timer.remaining(&4), Some(Duration::from_millis(15)));
+        assert_eq!(timer.len(), 4);
+        timer.resume(&3).unwrap();
+        timer.reschedule(&5, Duration::from_millis(3)).unwrap();
+        while let SimulationStep::Ok = timer.next() {
+            drain_fired(&fired, timer.current_time(), &mut sim_log);
+        }
+        assert_eq!(sim_log, expected);
+        assert!(timer.is_empty());
+    }
+
+    #[test]
+    fn zero_period_error_hook() {
+        let errors = Arc::new(Mutex::new(Vec::new()));
+        let action = Arc::new(Mutex::new(TimerErrorAction::Drop));
+        let mut timer = WheelTimer::<u64, _, _>::for_closures();
+        let (errors2, action2) = (errors.clone(), action.clone());
+        timer.set_error_hook(TimerErrorHook::custom(move |e| {
+            if let TimerError::ZeroPeriod(id) = e {
+                errors2.lock().push(*id);
+            }
+            *action2.lock()
+        }));
+
+        // rejected up front, before the first run
+        let fired = Arc::new(Mutex::new(0usize));
+        let f = fired.clone();
+        timer.schedule_action_periodic(1, Duration::from_millis(0), Duration::ZERO, move |_| {
+            *f.lock() += 1;
+            TimerReturn::Reschedule(())
+        });
+        assert_eq!(*errors.lock(), vec![1]);
+        assert_eq!(*fired.lock(), 0);
+        assert!(timer.is_empty());
+
+        // clamped to 1ms
+        *action.lock() = TimerErrorAction::Clamp;
+        let f = fired.clone();
+        timer.schedule_action_periodic(2, Duration::from_millis(0), Duration::ZERO, move |_| {
+            *f.lock() += 1;
+            TimerReturn::Reschedule(())
+        });
+        assert_eq!(*errors.lock(), vec![1, 2]);
+        assert_eq!(*fired.lock(), 1);
+        for _ in 0..5 {
+            timer.update(Duration::from_millis(1));
+        }
+        assert_eq!(*fired.lock(), 6);
+        assert_eq!(timer.remaining(&2), Some(Duration::from_millis(1)));
+    }
+
+    #[test]
+    fn update_budget_backlog() {
+        let fired = Arc::new(Mutex::new(Vec::new()));
+        let mut timer = WheelTimer::<u64, _, _>::for_closures();
+        timer.set_update_budget(UpdateBudget::callbacks(3));
+        for id in 0..10u64 {
+            let fired = fired.clone();
+            timer.schedule_action_once(id, Duration::from_millis(5), move |id| {
+                fired.lock().push(id);
+            });
+        }
+
+        timer.update(Duration::from_millis(10));
+        assert_eq!(*fired.lock(), vec![0, 1, 2]);
+        assert_eq!(timer.backlog(), 7);
+        assert_eq!(timer.len(), 7);
+        assert_eq!(timer.remaining(&5), Some(Duration::ZERO));
+
+        // the backlog can still be cancelled and rescheduled
+        timer.try_cancel(&3).unwrap();
+        timer.reschedule(&4, Duration::from_millis(5)).unwrap();
+        assert_eq!(timer.backlog(), 5);
+        assert_eq!(timer.len(), 6);
+
+        // no time needs to pass
+        timer.update(Duration::ZERO);
+        assert_eq!(*fired.lock(), vec![0, 1, 2, 5, 6, 7]);
+        timer.update(Duration::ZERO);
+        timer.update(Duration::from_millis(5));
+        assert_eq!(*fired.lock(), vec![0, 1, 2, 5, 6, 7, 8, 9, 4]);
+        assert!(timer.is_empty());
+    }
+
+    #[test]
+    fn catch_up_policies() {
+        let cases = [
+            (CatchUpPolicy::FireAll, UpdateBudget::UNLIMITED, 3, 5),
+            (CatchUpPolicy::FireAll, UpdateBudget::callbacks(2), 3, 5),
+            (CatchUpPolicy::FireOnce, UpdateBudget::UNLIMITED, 1, 10),
+            (CatchUpPolicy::SkipMissed, UpdateBudget::UNLIMITED, 1, 5),
+        ];
+        for (policy, budget, fires, left) in cases {
+            let fired = Arc::new(Mutex::new(0usize));
+            let mut timer = WheelTimer::<u64, _, _>::for_closures();
+            timer.set_catch_up(policy);
+            timer.set_update_budget(budget);
+            let f = fired.clone();
+            timer.schedule_action_periodic(
+                1,
+                Duration::from_millis(10),
+                Duration::from_millis(10),
+                move |_| {
+                    *f.lock() += 1;
+                    TimerReturn::Reschedule(())
+                },
+            );
+
+            // a long pause, due at 10, 20 and 30
+            timer.update(Duration::from_millis(35));
+            while timer.backlog() > 0 {
+                timer.update(Duration::ZERO);
+            }
+            assert_eq!(*fired.lock(), fires, "{:?}", policy);
+            assert_eq!(
+                timer.remaining(&1),
+                Some(Duration::from_millis(left)),
+                "{:?}",
+                policy
+            );
+        }
+    }
 }
@@ -57,7 +57,23 @@ where
     EntryType: CancellableTimerEntry + Send + Sync,
 {
     wheel: BasicQuadWheelWithOverflow<std::sync::Weak<EntryType>>,
-    timers: hashbrown::HashMap<EntryType::Id, std::sync::Arc<EntryType>>,
+    timers: hashbrown::HashMap<EntryType::Id, TimerSlot<EntryType>>,
+    paused: hashbrown::HashMap<EntryType::Id, PausedSlot<EntryType>>,
+
+    // ticks (ms) since creation
+    now: u128,
+}
+
+// An outstanding timeout and the tick it expires at
+struct TimerSlot<EntryType> {
+    entry: std::sync::Arc<EntryType>,
+    deadline: u128,
+}
+
+// A paused timeout and the delay left when it was paused
+struct PausedSlot<EntryType> {
+    entry: std::sync::Arc<EntryType>,
+    remaining: Duration,
 }
 
 impl<EntryType> QuadWheelWithOverflow<EntryType>
@@ -73,6 +89,11 @@ where
                     TimerError::Expired(e)
                 }
                 TimerError::NotFound => TimerError::NotFound,
+                TimerError::ZeroPeriod(rc_e) => {
+                    let e = std::sync::Arc::try_unwrap(rc_e).unwrap();
+                    TimerError::ZeroPeriod(e)
+                }
+                TimerError::Disconnected => TimerError::Disconnected,
             })
     }
 
@@ -95,6 +116,8 @@ where
         QuadWheelWithOverflow {
             wheel: BasicQuadWheelWithOverflow::new(rc_prune::<EntryType>),
             timers: hashbrown::HashMap::new(),
+            paused: hashbrown::HashMap::new(),
+            now: 0u128,
         }
     }
 
@@ -109,11 +132,17 @@ where
 
         match self.wheel.insert_with_delay(weak_e, delay) {
             Ok(_) => {
-                self.timers.insert(e.id().clone(), e);
+                let deadline = self.now + delay.as_millis();
+                self.paused.remove(e.id());
+                self.timers
+                    .insert(e.id().clone(), TimerSlot { entry: e, deadline });
                 Ok(())
             }
             Err(TimerError::Expired(_weak_e)) => Err(TimerError::Expired(e)),
-            Err(TimerError::NotFound) => Err(TimerError::NotFound), // not that this can happen here, but it makes the compiler happy
+            // not that these can happen here, but it makes the compiler happy
+            Err(TimerError::ZeroPeriod(_weak_e)) => Err(TimerError::ZeroPeriod(e)),
+            Err(TimerError::NotFound) => Err(TimerError::NotFound),
+            Err(TimerError::Disconnected) => Err(TimerError::Disconnected),
         }
     }
 
@@ -127,6 +156,100 @@ where
         // This will prevent the Weak pointer in the wheels from upgrading later
         match self.timers.remove_entry(id) {
             Some(_) => Ok(()),
+            None => match self.paused.remove_entry(id) {
+                Some(_) => Ok(()),
+                None => Err(TimerError::NotFound),
+            },
+        }
+    }
+
+    /// Time left until the timeout with the given `id` expires
+    ///
+    /// For a paused timeout this is the delay left when it was paused.
+    pub fn remaining(&self, id: &EntryType::Id) -> Option<Duration> {
+        match self.timers.get(id) {
+            Some(slot) => {
+                let left = slot.deadline.saturating_sub(self.now);
+                Some(Duration::from_millis(left as u64))
+            }
+            None => self.paused.get(id).map(|slot| slot.remaining),
+        }
+    }
+
+    /// Number of outstanding timeouts, including paused ones
+    pub fn len(&self) -> usize {
+        self.timers.len() + self.paused.len()
+    }
+
+    /// Whether there are no outstanding timeouts
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// Whether the timeout with the given `id` is paused
+    pub fn is_paused(&self, id: &EntryType::Id) -> bool {
+        self.paused.contains_key(id)
+    }
+
+    /// Move the timeout with the given `id` to expire after `delay` from now
+    ///
+    /// A paused timeout stays paused, with `delay` left once it's resumed.
+    /// If `delay` is zero the entry is removed and returned as [TimerError::Expired].
+    pub fn reschedule(
+        &mut self,
+        id: &EntryType::Id,
+        delay: Duration,
+    ) -> Result<(), TimerError<std::sync::Arc<EntryType>>> {
+        if let Some(slot) = self.paused.get_mut(id) {
+            if delay.as_millis() == 0 {
+                let slot = self.paused.remove(id).unwrap();
+                return Err(TimerError::Expired(slot.entry));
+            }
+            slot.remaining = delay;
+            return Ok(());
+        }
+
+        // The old wheel slot is left in place, it's skipped once it expires
+        // because the deadline doesn't match anymore
+        match self.timers.remove(id) {
+            Some(slot) => self.insert_ref_with_delay(slot.entry, delay),
+            None => Err(TimerError::NotFound),
+        }
+    }
+
+    /// Stop the clock for the timeout with the given `id`
+    ///
+    /// The timeout does not expire until it's [resumed](QuadWheelWithOverflow::resume).
+    pub fn pause(&mut self, id: &EntryType::Id) -> Result<(), TimerError<Infallible>> {
+        if self.paused.contains_key(id) {
+            return Ok(());
+        }
+        match self.timers.remove(id) {
+            Some(slot) => {
+                let left = slot.deadline.saturating_sub(self.now);
+                let remaining = Duration::from_millis(std::cmp::max(left, 1) as u64);
+                self.paused.insert(
+                    id.clone(),
+                    PausedSlot {
+                        entry: slot.entry,
+                        remaining,
+                    },
+                );
+                Ok(())
+            }
+            None => Err(TimerError::NotFound),
+        }
+    }
+
+    /// Restart the clock for a paused timeout with the given `id`
+    ///
+    /// It expires after the delay that was left when it was paused.
+    pub fn resume(&mut self, id: &EntryType::Id) -> Result<(), TimerError<Infallible>> {
+        match self.paused.remove(id) {
+            Some(slot) => match self.insert_ref_with_delay(slot.entry, slot.remaining) {
+                Ok(_) => Ok(()),
+                Err(_) => unreachable!("Paused timer entry with 0ms left!"),
+            },
             None => Err(TimerError::NotFound),
         }
     }
@@ -137,10 +260,16 @@ where
     ) -> Option<std::sync::Arc<EntryType>> {
         match weak_e.upgrade() {
             Some(rc_e) => {
-                match self.timers.remove_entry(rc_e.id()) {
-                    Some(rc_e2) => drop(rc_e2), // ok
+                match self.timers.get(rc_e.id()) {
+                    Some(slot) if slot.deadline > self.now => {
+                        // Stale slot left behind by reschedule() or resume()
+                        return None;
+                    }
+                    Some(_) => {
+                        self.timers.remove(rc_e.id()); // ok
+                    }
                     None => {
-                        // Perhaps it was removed via cancel(), and the underlying
+                        // Perhaps it was removed via cancel() or paused, and the underlying
                         // Arc is still alive through some other reference
                         return None;
                     }
@@ -156,6 +285,7 @@ where
     /// Returns a list of all timers that expire during this tick.
     pub fn tick(&mut self) -> Vec<std::sync::Arc<EntryType>> {
         let res = self.wheel.tick();
+        self.now += 1u128;
         res.into_iter()
             .flat_map(|weak_e| self.take_timer(weak_e))
             .collect()
@@ -168,6 +298,7 @@ where
     /// valid with [can_skip](QuadWheelWithOverflow::can_skip)!
     pub fn skip(&mut self, amount: u32) {
         self.wheel.skip(amount);
+        self.now += amount as u128;
     }
 
     /// Determine if and how many ticks can be skipped
@@ -458,6 +589,60 @@ mod u64_tests {
         assert_eq!(items.len(), 2);
     }
 
+    #[test]
+    fn remaining_reschedule_pause() {
+        let mut timer = QuadWheelWithOverflow::new();
+        for (id, delay) in [(1u64, 10u64), (2, 20), (3, 300)] {
+            timer
+                .insert(IdOnlyTimerEntry {
+                    id,
+                    delay: Duration::from_millis(delay),
+                })
+                .expect("Could not insert timer entry!");
+        }
+        assert_eq!(timer.len(), 3);
+        assert_eq!(timer.remaining(&1), Some(Duration::from_millis(10)));
+        assert_eq!(timer.remaining(&4), None);
+
+        timer.skip(4);
+        assert_eq!(timer.remaining(&1), Some(Duration::from_millis(6)));
+
+        // move 1 after 2, pause 2
+        timer
+            .reschedule(&1, Duration::from_millis(30))
+            .expect("Entry could not be rescheduled!");
+        timer.pause(&2).expect("Entry could not be paused!");
+        assert!(timer.is_paused(&2));
+        assert!(timer.reschedule(&4, Duration::from_millis(1)).is_err());
+        assert!(timer.resume(&1).is_err());
+
+        let mut fired = vec![];
+        for t in 5..=40 {
+            for e in timer.tick() {
+                fired.push((t, *e.id()));
+            }
+        }
+        assert_eq!(fired, vec![(34, 1)]);
+        assert_eq!(timer.remaining(&2), Some(Duration::from_millis(16)));
+
+        timer.resume(&2).expect("Entry could not be resumed!");
+        assert!(!timer.is_paused(&2));
+        assert_eq!(timer.remaining(&2), Some(Duration::from_millis(16)));
+        for t in 41..=56 {
+            for e in timer.tick() {
+                fired.push((t, *e.id()));
+            }
+        }
+        assert_eq!(fired, vec![(34, 1), (56, 2)]);
+
+        // zero delay hands the entry back
+        match timer.reschedule(&3, Duration::from_millis(0)) {
+            Err(TimerError::Expired(e)) => assert_eq!(e.id(), &3),
+            res => panic!("Unexpected result {:?}", res),
+        }
+        assert!(timer.is_empty());
+    }
+
     #[test]
     fn single_ms_reschedule() {
         let mut timer = QuadWheelWithOverflow::new();
@@ -1,5 +1,8 @@
 //! Commlib: event, log, service, ...
 
+// `#[derive(Event)]` expands to `::commlib::...` paths, make them resolve inside this crate too
+extern crate self as commlib;
+
 ///
 #[macro_use]
 mod macros;
@@ -15,10 +18,42 @@ pub use stop_watch::StopWatch;
 mod commlib_event;
 pub use commlib_event::*;
 
+/// `#[derive(Event)]`
+pub use commlib_derive::Event;
+
+///
+mod commlib_event_queue;
+pub use commlib_event_queue::EventQueue;
+
+///
+mod commlib_event_stats;
+pub use commlib_event_stats::{
+    dump_event_stats, register_event_stats, EventStats, HandlerStats, DEFAULT_EVENT_SLOW_THRESHOLD,
+};
+
+///
+mod commlib_event_bus;
+pub use commlib_event_bus::*;
+
+///
+mod signal_service;
+pub use signal_service::{Signal, SignalDisposition, SignalEvent, SignalService, MAX_SIGNO};
+
 ///
 mod clock;
 pub use clock::*;
 
+// commlib 自己的事件
+assert_unique_event_ids!(SignalEvent, ClockJumpEvent);
+
+///
+mod cron;
+pub use cron::CronSchedule;
+
+///
+mod time_source;
+pub use time_source::{ManualTimeSource, OffsetTimeSource, SystemTimeSource, TimeSource};
+
 ///
 pub mod hash_wheel_timer;
 
@@ -33,3 +68,6 @@ pub use globals::*;
 /// 通用定义
 mod commlib_def;
 pub use commlib_def::*;
+
+#[cfg(test)]
+mod test_logger;
@@ -0,0 +1,375 @@
+//!
+//! Commlib: SignalService
+//!
+//! 信号处理函数里只做 async-signal-safe 的事情：置位原子标志。
+//! 真正的处理在持有 SignalService 的线程上通过 poll 完成，以 Event 或 channel 的方式投递。
+//!
+
+use std::collections::BTreeMap;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::mpsc::Sender;
+
+use commlib_sys::sig;
+use commlib_sys::SignalCallback;
+
+use crate::Event;
+
+/// 信号编号上限（不含），覆盖 linux 的实时信号
+pub const MAX_SIGNO: i32 = 65;
+
+/// 进程信号对应的动作
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Signal {
+    /// SIGINT, SIGTERM, SIGABRT, SIGQUIT (SIGBREAK on windows)
+    Terminate,
+    /// SIGUSR1: 关服
+    Shutdown,
+    /// SIGUSR2: 热更新配置
+    ReloadConfig,
+    /// 自定义动作
+    Named(&'static str),
+}
+
+/// 信号当前的处理方式
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SignalDisposition {
+    Default,
+    Ignore,
+    Handler,
+}
+
+/// 信号事件，在 SignalService 所在线程上触发
+#[derive(Event)]
+#[event(namespace = "commlib")]
+pub struct SignalEvent {
+    pub signo: i32,
+    pub signal: Signal,
+}
+
+static G_SIGNAL_PENDING: [AtomicBool; MAX_SIGNO as usize] =
+    [const { AtomicBool::new(false) }; MAX_SIGNO as usize];
+
+static G_SIGNAL_SERVICE_INSTALLED: AtomicBool = AtomicBool::new(false);
+
+extern "C" fn on_signal(signo: i32) {
+    if let Some(flag) = pending_flag(signo) {
+        flag.store(true, Ordering::SeqCst);
+    }
+}
+
+#[inline(always)]
+fn pending_flag(signo: i32) -> Option<&'static AtomicBool> {
+    if signo > 0 && signo < MAX_SIGNO {
+        Some(&G_SIGNAL_PENDING[signo as usize])
+    } else {
+        None
+    }
+}
+
+#[inline(always)]
+fn check_signo(signo: i32) -> Result<(), String> {
+    match pending_flag(signo) {
+        Some(_) => Ok(()),
+        None => Err(format!(
+            "signal {} is out of range [1, {})",
+            signo, MAX_SIGNO
+        )),
+    }
+}
+
+/// 信号服务：进程内只能有一个，信号在持有者线程上 poll 时投递；drop 时已注册的信号恢复默认处理
+pub struct SignalService {
+    actions: BTreeMap<i32, Signal>,
+    sender: Option<Sender<Signal>>,
+    _not_send: std::marker::PhantomData<*const ()>,
+}
+
+impl SignalService {
+    /// 安装默认的信号处理：退出，关服，热更新配置，并忽略 SIGPIPE
+    pub fn install() -> Result<Self, String> {
+        let mut service = Self::install_empty()?;
+
+        service.register(sig::SIGINT, Signal::Terminate)?;
+        service.register(sig::SIGTERM, Signal::Terminate)?;
+        service.register(sig::SIGABRT, Signal::Terminate)?;
+
+        #[cfg(windows)]
+        service.register(sig::SIGBREAK, Signal::Terminate)?;
+
+        #[cfg(unix)]
+        {
+            service.register(sig::SIGQUIT, Signal::Terminate)?;
+            service.ignore(sig::SIGPIPE)?;
+            service.register(sig::SIGUSR1, Signal::Shutdown)?;
+            service.register(sig::SIGUSR2, Signal::ReloadConfig)?;
+        }
+
+        Ok(service)
+    }
+
+    /// 不安装任何信号处理，由调用者自行 register
+    pub fn install_empty() -> Result<Self, String> {
+        if G_SIGNAL_SERVICE_INSTALLED.swap(true, Ordering::SeqCst) {
+            return Err("signal service is already installed".to_owned());
+        }
+
+        // 丢弃安装之前残留的信号
+        for flag in &G_SIGNAL_PENDING {
+            flag.store(false, Ordering::SeqCst);
+        }
+
+        Ok(Self {
+            actions: BTreeMap::new(),
+            sender: None,
+            _not_send: std::marker::PhantomData,
+        })
+    }
+
+    /// 把信号映射到动作（sigaction），可用于 SIGHUP, SIGCHLD, SIGALRM 及实时信号等
+    pub fn register(&mut self, signo: i32, action: Signal) -> Result<(), String> {
+        check_signo(signo)?;
+        sig::install_signal_handler(signo, SignalCallback(on_signal)).map_err(|e| e.to_string())?;
+        self.actions.insert(signo, action);
+        Ok(())
+    }
+
+    /// 忽略信号
+    pub fn ignore(&mut self, signo: i32) -> Result<(), String> {
+        check_signo(signo)?;
+        sig::ignore_signal(signo).map_err(|e| e.to_string())?;
+        self.unregister(signo);
+        Ok(())
+    }
+
+    /// 恢复信号的默认处理
+    pub fn restore_default(&mut self, signo: i32) -> Result<(), String> {
+        check_signo(signo)?;
+        sig::restore_default_signal(signo).map_err(|e| e.to_string())?;
+        self.unregister(signo);
+        Ok(())
+    }
+
+    // 丢弃未处理的信号，重新注册时不会投递过期的信号
+    fn unregister(&mut self, signo: i32) {
+        self.actions.remove(&signo);
+        G_SIGNAL_PENDING[signo as usize].store(false, Ordering::SeqCst);
+    }
+
+    /// 信号映射的动作
+    pub fn action(&self, signo: i32) -> Option<Signal> {
+        self.actions.get(&signo).copied()
+    }
+
+    /// 已注册的信号和动作，按信号编号排序
+    pub fn registered(&self) -> Vec<(i32, Signal)> {
+        self.actions.iter().map(|(k, v)| (*k, *v)).collect()
+    }
+
+    /// 查询系统中信号当前的处理方式
+    pub fn disposition(signo: i32) -> Result<SignalDisposition, String> {
+        match sig::query_signal_disposition(signo).map_err(|e| e.to_string())? {
+            sig::SIGNAL_DISPOSITION_DEFAULT => Ok(SignalDisposition::Default),
+            sig::SIGNAL_DISPOSITION_IGNORE => Ok(SignalDisposition::Ignore),
+            _ => Ok(SignalDisposition::Handler),
+        }
+    }
+
+    /// 实时信号范围，不支持时返回 None
+    pub fn realtime_range() -> Option<std::ops::RangeInclusive<i32>> {
+        let (min, max) = (sig::signal_rtmin(), sig::signal_rtmax());
+        if min > 0 && max >= min {
+            Some(min..=std::cmp::min(max, MAX_SIGNO - 1))
+        } else {
+            None
+        }
+    }
+
+    /// 除了触发 SignalEvent，同时把信号发送到 channel
+    pub fn set_sender(&mut self, sender: Sender<Signal>) {
+        self.sender = Some(sender);
+    }
+
+    /// 取出所有未处理的信号（同一信号多次到达只算一次），按信号编号排序
+    pub fn take_pending(&self) -> Vec<(i32, Signal)> {
+        self.actions
+            .iter()
+            .filter(|(signo, _)| G_SIGNAL_PENDING[**signo as usize].swap(false, Ordering::SeqCst))
+            .map(|(signo, action)| (*signo, *action))
+            .collect()
+    }
+
+    /// 在当前线程投递未处理的信号，返回投递的信号数量
+    pub fn poll(&mut self) -> usize {
+        let pending = self.take_pending();
+        for (signo, signal) in &pending {
+            if let Some(sender) = &self.sender {
+                if sender.send(*signal).is_err() {
+                    // receiver is gone
+                    self.sender = None;
+                }
+            }
+
+            SignalEvent {
+                signo: *signo,
+                signal: *signal,
+            }
+            .trigger();
+        }
+        pending.len()
+    }
+}
+
+impl Drop for SignalService {
+    fn drop(&mut self) {
+        // 恢复默认处理，否则服务销毁后信号只置位没人 poll 的标志，Ctrl-C 和 kill 都无法结束进程
+        let signos: Vec<i32> = self.actions.keys().copied().collect();
+        for signo in signos {
+            if let Err(e) = self.restore_default(signo) {
+                log::error!("restore default handler of signal {} failed: {}", signo, e);
+            }
+        }
+        G_SIGNAL_SERVICE_INSTALLED.store(false, Ordering::SeqCst);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::cell::RefCell;
+    use std::rc::Rc;
+    use std::sync::Mutex;
+
+    use super::*;
+
+    // the service is process wide
+    static TEST_LOCK: Mutex<()> = Mutex::new(());
+
+    #[test]
+    fn test_signal_service() {
+        let _lock = TEST_LOCK.lock().unwrap_or_else(|e| e.into_inner());
+
+        let mut service = SignalService::install().unwrap();
+        assert!(SignalService::install().is_err());
+
+        let (tx, rx) = std::sync::mpsc::channel();
+        service.set_sender(tx);
+
+        let received = Rc::new(RefCell::new(Vec::new()));
+        let received2 = received.clone();
+        SignalEvent::add_callback(move |e| received2.borrow_mut().push((e.signo, e.signal)));
+
+        assert_eq!(service.poll(), 0);
+
+        // simulate signal delivery from another thread
+        std::thread::spawn(|| {
+            on_signal(sig::SIGTERM);
+            on_signal(sig::SIGTERM);
+            on_signal(sig::SIGINT);
+        })
+        .join()
+        .unwrap();
+
+        assert_eq!(service.poll(), 2);
+        assert_eq!(
+            *received.borrow(),
+            vec![
+                (sig::SIGINT, Signal::Terminate),
+                (sig::SIGTERM, Signal::Terminate)
+            ]
+        );
+        assert_eq!(
+            rx.try_iter().collect::<Vec<_>>(),
+            vec![Signal::Terminate, Signal::Terminate]
+        );
+        assert_eq!(service.poll(), 0);
+
+        #[cfg(unix)]
+        {
+            on_signal(sig::SIGUSR2);
+            assert_eq!(
+                service.take_pending(),
+                vec![(sig::SIGUSR2, Signal::ReloadConfig)]
+            );
+            assert_eq!(service.poll(), 0);
+        }
+
+        drop(service);
+        assert_eq!(
+            SignalService::disposition(sig::SIGTERM),
+            Ok(SignalDisposition::Default)
+        );
+        let service = SignalService::install().unwrap();
+        assert!(service.take_pending().is_empty());
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_signal_mapping() {
+        let _lock = TEST_LOCK.lock().unwrap_or_else(|e| e.into_inner());
+
+        let mut service = SignalService::install_empty().unwrap();
+        assert!(service.registered().is_empty());
+
+        service
+            .register(sig::SIGHUP, Signal::Named("reopen_log"))
+            .unwrap();
+        service
+            .register(sig::SIGALRM, Signal::Named("alarm"))
+            .unwrap();
+        assert_eq!(
+            SignalService::disposition(sig::SIGHUP),
+            Ok(SignalDisposition::Handler)
+        );
+        assert_eq!(
+            service.registered(),
+            vec![
+                (sig::SIGHUP, Signal::Named("reopen_log")),
+                (sig::SIGALRM, Signal::Named("alarm"))
+            ]
+        );
+
+        // signals without an action are dropped
+        on_signal(sig::SIGHUP);
+        on_signal(sig::SIGUSR1);
+        assert_eq!(
+            service.take_pending(),
+            vec![(sig::SIGHUP, Signal::Named("reopen_log"))]
+        );
+
+        on_signal(sig::SIGALRM);
+        service.ignore(sig::SIGALRM).unwrap();
+        assert_eq!(
+            SignalService::disposition(sig::SIGALRM),
+            Ok(SignalDisposition::Ignore)
+        );
+        assert_eq!(service.action(sig::SIGALRM), None);
+
+        // no stale signal after registering again
+        service
+            .register(sig::SIGALRM, Signal::Named("alarm"))
+            .unwrap();
+        assert!(service.take_pending().is_empty());
+
+        service.restore_default(sig::SIGHUP).unwrap();
+        service.restore_default(sig::SIGALRM).unwrap();
+        assert_eq!(
+            SignalService::disposition(sig::SIGHUP),
+            Ok(SignalDisposition::Default)
+        );
+        assert!(service.registered().is_empty());
+
+        // out of range or not catchable
+        assert!(service.register(0, Signal::Named("zero")).is_err());
+        assert!(service.register(MAX_SIGNO, Signal::Named("big")).is_err());
+        assert!(service.register(9, Signal::Named("kill")).is_err());
+
+        #[cfg(target_os = "linux")]
+        {
+            let rt = SignalService::realtime_range().unwrap();
+            let signo = *rt.start() + 1;
+            service.register(signo, Signal::Named("rt")).unwrap();
+            on_signal(signo);
+            assert_eq!(service.poll(), 1);
+            service.restore_default(signo).unwrap();
+        }
+    }
+}
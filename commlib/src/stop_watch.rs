@@ -23,6 +23,11 @@ impl StopWatch {
         self.start.elapsed().as_millis()
     }
 
+    /// 精确耗时
+    pub fn elapsed_duration(&self) -> std::time::Duration {
+        self.start.elapsed()
+    }
+
     ///
     pub fn elapsed_and_reset(&mut self) -> u128 {
         let now = std::time::Instant::now();
@@ -0,0 +1,55 @@
+//!
+//! Test logger: captures log records of the current thread
+//!
+
+use std::cell::RefCell;
+use std::sync::Once;
+
+/// Captured log record
+#[derive(Debug, Clone)]
+pub struct CapturedRecord {
+    pub level: log::Level,
+    pub target: String,
+    pub message: String,
+}
+
+struct TestLogger;
+
+thread_local! {
+    static G_CAPTURED: RefCell<Vec<CapturedRecord>> = const { RefCell::new(Vec::new()) };
+}
+
+impl log::Log for TestLogger {
+    fn enabled(&self, _metadata: &log::Metadata) -> bool {
+        true
+    }
+
+    fn log(&self, record: &log::Record) {
+        G_CAPTURED.with(|g| {
+            g.borrow_mut().push(CapturedRecord {
+                level: record.level(),
+                target: record.target().to_owned(),
+                message: record.args().to_string(),
+            })
+        });
+    }
+
+    fn flush(&self) {}
+}
+
+static LOGGER: TestLogger = TestLogger;
+static INIT: Once = Once::new();
+
+/// Install the test logger and clear records captured on current thread
+pub fn init() {
+    INIT.call_once(|| {
+        log::set_logger(&LOGGER).expect("another logger is installed");
+        log::set_max_level(log::LevelFilter::Trace);
+    });
+    G_CAPTURED.with(|g| g.borrow_mut().clear());
+}
+
+/// Take records captured on current thread
+pub fn take() -> Vec<CapturedRecord> {
+    G_CAPTURED.with(|g| std::mem::take(&mut *g.borrow_mut()))
+}
@@ -0,0 +1,180 @@
+//!
+//! Time source
+//!
+//! Clock 的时间来源：系统时间，手动控制的虚拟时间（测试），以及带偏移的时间（GM 调时间）。
+//!
+//! 每个时间来源同时提供墙上时间和单调时间，时间轮由单调时间驱动，不受调整系统时间的影响。
+//!
+
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::Arc;
+use std::time::{Duration, Instant, SystemTime};
+
+/// 时间来源
+pub trait TimeSource {
+    /// 当前 unix 时间（ms），可能因为调整系统时间而跳变
+    fn now_ms(&self) -> u64;
+
+    /// 单调时间（ms），起点任意，永不回退
+    fn monotonic_ms(&self) -> u64;
+}
+
+impl<S: TimeSource + ?Sized> TimeSource for Box<S> {
+    #[inline(always)]
+    fn now_ms(&self) -> u64 {
+        (**self).now_ms()
+    }
+
+    #[inline(always)]
+    fn monotonic_ms(&self) -> u64 {
+        (**self).monotonic_ms()
+    }
+}
+
+/// 系统时间，单调时间来自 [`Instant`]
+#[derive(Debug, Clone, Copy)]
+pub struct SystemTimeSource {
+    origin: Instant,
+}
+
+impl SystemTimeSource {
+    /// 单调时间从现在开始计算
+    pub fn new() -> Self {
+        Self {
+            origin: Instant::now(),
+        }
+    }
+}
+
+impl Default for SystemTimeSource {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl TimeSource for SystemTimeSource {
+    #[inline(always)]
+    fn now_ms(&self) -> u64 {
+        SystemTime::now()
+            .duration_since(SystemTime::UNIX_EPOCH)
+            .map(|d| d.as_millis() as u64)
+            .unwrap_or(0)
+    }
+
+    #[inline(always)]
+    fn monotonic_ms(&self) -> u64 {
+        self.origin.elapsed().as_millis() as u64
+    }
+}
+
+/// 手动控制的虚拟时间，只有调用 set/advance 才会变化
+///
+/// clone 出来的副本共享同一个时间，可以一份交给 Clock，一份留在测试里拨动。
+#[derive(Debug, Clone, Default)]
+pub struct ManualTimeSource {
+    now: Arc<AtomicU64>,
+    monotonic: Arc<AtomicU64>,
+}
+
+impl ManualTimeSource {
+    /// 从 start_ms 开始
+    pub fn new(start_ms: u64) -> Self {
+        Self {
+            now: Arc::new(AtomicU64::new(start_ms)),
+            monotonic: Arc::new(AtomicU64::new(0)),
+        }
+    }
+
+    /// 调整墙上时间，可以往回拨，单调时间不变（模拟调整系统时间）
+    pub fn set(&self, now_ms: u64) {
+        self.now.store(now_ms, Ordering::SeqCst);
+    }
+
+    /// 时间流逝 d，墙上时间和单调时间一起前进
+    pub fn advance(&self, d: Duration) {
+        let ms = d.as_millis() as u64;
+        self.now.fetch_add(ms, Ordering::SeqCst);
+        self.monotonic.fetch_add(ms, Ordering::SeqCst);
+    }
+}
+
+impl TimeSource for ManualTimeSource {
+    #[inline(always)]
+    fn now_ms(&self) -> u64 {
+        self.now.load(Ordering::SeqCst)
+    }
+
+    #[inline(always)]
+    fn monotonic_ms(&self) -> u64 {
+        self.monotonic.load(Ordering::SeqCst)
+    }
+}
+
+/// 在另一个时间来源的墙上时间上加一个偏移（ms，可以为负），单调时间不变
+#[derive(Debug, Clone, Default)]
+pub struct OffsetTimeSource<S> {
+    inner: S,
+    offset: i64,
+}
+
+impl<S: TimeSource> OffsetTimeSource<S> {
+    /// 偏移 offset ms
+    pub fn new(inner: S, offset: i64) -> Self {
+        Self { inner, offset }
+    }
+
+    /// 当前偏移
+    pub fn offset(&self) -> i64 {
+        self.offset
+    }
+
+    /// 设置偏移
+    pub fn set_offset(&mut self, offset: i64) {
+        self.offset = offset;
+    }
+
+    /// 被偏移的时间来源
+    pub fn inner(&self) -> &S {
+        &self.inner
+    }
+}
+
+impl<S: TimeSource> TimeSource for OffsetTimeSource<S> {
+    #[inline(always)]
+    fn now_ms(&self) -> u64 {
+        let now = self.inner.now_ms() as i64 + self.offset;
+        std::cmp::max(now, 0) as u64
+    }
+
+    #[inline(always)]
+    fn monotonic_ms(&self) -> u64 {
+        self.inner.monotonic_ms()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_time_sources() {
+        let manual = ManualTimeSource::new(1000);
+        let shared = manual.clone();
+        shared.advance(Duration::from_millis(500));
+        assert_eq!(manual.now_ms(), 1500);
+        shared.set(200);
+        assert_eq!(manual.now_ms(), 200);
+        assert_eq!(manual.monotonic_ms(), 500);
+
+        let mut offset = OffsetTimeSource::new(manual, 3 * 86400 * 1000);
+        assert_eq!(offset.now_ms(), 200 + 3 * 86400 * 1000);
+        offset.set_offset(-1000);
+        assert_eq!(offset.offset(), -1000);
+        assert_eq!(offset.now_ms(), 0);
+        assert_eq!(offset.monotonic_ms(), 500);
+
+        let boxed: Box<dyn TimeSource> = Box::new(SystemTimeSource::new());
+        assert!(boxed.now_ms() > 1_600_000_000_000);
+        assert!(boxed.monotonic_ms() < 1000);
+    }
+}
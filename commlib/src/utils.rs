@@ -1,3 +1,7 @@
+///
+mod crypto_error;
+pub use self::crypto_error::CryptoError;
+
 ///
 mod rand_bytes;
 pub use self::rand_bytes::{rand_bytes, rand_bytes2};
@@ -8,12 +12,40 @@ pub use self::base64::Base64;
 
 ///
 mod blowfish;
-pub use self::blowfish::Blowfish;
+pub use self::blowfish::{Blowfish, BlowfishStream, BLOWFISH_KEY_MAX, BLOWFISH_KEY_MIN};
+
+///
+mod crc32;
+pub use self::crc32::*;
+
+///
+mod hasher;
+pub use self::hasher::Hasher;
+
+///
+mod hmac;
+pub use self::hmac::*;
+
+///
+mod keccak;
+pub use self::keccak::*;
 
 ///
 mod md5;
 pub use self::md5::*;
 
+///
+mod sha1;
+pub use self::sha1::*;
+
+///
+mod sha256;
+pub use self::sha256::*;
+
+///
+mod sha3;
+pub use self::sha3::*;
+
 ///
 mod rand;
 pub use self::rand::*;
@@ -24,4 +56,7 @@ pub use self::string::*;
 
 ///
 mod thread_pool;
-pub use thread_pool::{Builder as ThreadPoolBuilder, ThreadPool};
+pub use thread_pool::{
+    panic_message, Builder as ThreadPoolBuilder, DrainPolicy, JobHandle, PanicHandler,
+    Scope as ThreadPoolScope, ShutdownReport, ThreadPool,
+};
@@ -2,33 +2,93 @@
 //! Commlib: Blowfish
 //!
 
+use cxx::SharedPtr;
+
 use commlib_sys::crypto::{
-    blowfish_decrypt, blowfish_encrypt, blowfish_set_init_vec, blowfish_set_key, new_blowfish,
+    blowfish_decrypt, blowfish_decrypt_in_place, blowfish_encrypt, blowfish_encrypt_in_place,
+    blowfish_set_init_vec, blowfish_set_key, new_blowfish, BlowfishCfb64, BlowfishKeyLen,
 };
+
+use super::CryptoError;
+
+/// Blowfish key length range in bytes (32 ~ 448 bits), the same values as the C++ side
+pub const BLOWFISH_KEY_MIN: usize = BlowfishKeyLen::Min.repr as usize;
+pub const BLOWFISH_KEY_MAX: usize = BlowfishKeyLen::Max.repr as usize;
+
 pub struct Blowfish();
 
 impl Blowfish {
     ///
-    pub fn encrypt(key: &[u8], iv: u64, plaintext: &[u8]) -> Result<Vec<u8>, String> {
-        let cipher = new_blowfish();
-        blowfish_set_key(cipher.clone(), key);
-        blowfish_set_init_vec(cipher.clone(), iv);
-
-        let ciphertext = blowfish_encrypt(cipher.clone(), plaintext);
+    pub fn encrypt(key: &[u8], iv: u64, plaintext: &[u8]) -> Result<Vec<u8>, CryptoError> {
+        let cipher = new_cipher(key, iv)?;
+        let ciphertext = blowfish_encrypt(cipher, plaintext)?;
         Ok(ciphertext)
     }
 
     ///
-    pub fn decrypt(key: &[u8], iv: u64, ciphertext: &[u8]) -> Result<Vec<u8>, String> {
-        let cipher = new_blowfish();
-        blowfish_set_key(cipher.clone(), key);
-        blowfish_set_init_vec(cipher.clone(), iv);
-
-        let plaintext = blowfish_decrypt(cipher.clone(), ciphertext);
+    pub fn decrypt(key: &[u8], iv: u64, ciphertext: &[u8]) -> Result<Vec<u8>, CryptoError> {
+        let cipher = new_cipher(key, iv)?;
+        let plaintext = blowfish_decrypt(cipher, ciphertext)?;
         Ok(plaintext)
     }
 }
 
+fn new_cipher(key: &[u8], iv: u64) -> Result<SharedPtr<BlowfishCfb64>, CryptoError> {
+    if key.len() < BLOWFISH_KEY_MIN || key.len() > BLOWFISH_KEY_MAX {
+        return Err(CryptoError::BadKey(format!(
+            "blowfish key length {} is out of range [{}, {}]",
+            key.len(),
+            BLOWFISH_KEY_MIN,
+            BLOWFISH_KEY_MAX
+        )));
+    }
+
+    let cipher = new_blowfish()?;
+    blowfish_set_key(cipher.clone(), key).map_err(|e| CryptoError::BadKey(e.what().to_owned()))?;
+    blowfish_set_init_vec(cipher.clone(), iv);
+    Ok(cipher)
+}
+
+/// Blowfish CFB64 stream cipher, keyed once and used for a whole connection
+///
+/// The feedback register and the byte offset inside the current block are kept
+/// across calls, so a stream split into packets of any size gives the same bytes
+/// as [`Blowfish::encrypt`] over the concatenated stream. Each direction of a
+/// connection needs its own `BlowfishStream`.
+pub struct BlowfishStream {
+    cipher: SharedPtr<BlowfishCfb64>,
+}
+
+// The C++ cipher is only reachable through this wrapper, which never shares its pointer.
+unsafe impl Send for BlowfishStream {}
+
+impl BlowfishStream {
+    /// Key the cipher and set the initial feedback register
+    pub fn new(key: &[u8], iv: u64) -> Result<Self, CryptoError> {
+        let cipher = new_cipher(key, iv)?;
+        Ok(Self { cipher })
+    }
+
+    /// Restart the stream with a new init vector, keeping the key
+    pub fn reset(&mut self, iv: u64) {
+        blowfish_set_init_vec(self.cipher.clone(), iv);
+    }
+
+    /// Encrypt the next slice of the stream in place
+    #[inline(always)]
+    pub fn encrypt(&mut self, data: &mut [u8]) -> Result<(), CryptoError> {
+        blowfish_encrypt_in_place(self.cipher.clone(), data)?;
+        Ok(())
+    }
+
+    /// Decrypt the next slice of the stream in place
+    #[inline(always)]
+    pub fn decrypt(&mut self, data: &mut [u8]) -> Result<(), CryptoError> {
+        blowfish_decrypt_in_place(self.cipher.clone(), data)?;
+        Ok(())
+    }
+}
+
 /*
 use openssl::symm::{decrypt as ossl_decrypt, encrypt as ossl_encrtypt, Cipher};
 
@@ -81,7 +141,7 @@ impl Blowfish {
 mod tests {
     use hex::{self, FromHex};
 
-    use crate::utils::Blowfish;
+    use crate::utils::{Blowfish, BlowfishStream, CryptoError};
 
     #[test]
     pub fn test_bf_cfb64() {
@@ -103,4 +163,88 @@ mod tests {
         assert_eq!(plaintext.as_slice(), pt.as_slice());
         assert_eq!(ciphertext.as_slice(), ct.as_slice());
     }
+
+    #[test]
+    pub fn test_bf_cfb64_stream() {
+        let pt = "37363534333231204E6F77206973207468652074696D6520666F722000";
+        let ct = "E73214A2822139CAF26ECF6D2EB9E76E3DA3DE04D1517200519D57A6C3";
+        let key = Vec::from_hex("0123456789ABCDEFF0E1D2C3B4A59687").unwrap();
+        let iv = 0xFEDCBA9876543210_u64;
+
+        let pt = Vec::from_hex(pt).unwrap();
+        let ct = Vec::from_hex(ct).unwrap();
+
+        // odd packet sizes, so packets cross block boundaries
+        for packet_size in [1, 3, 5, 8, 13] {
+            let mut enc = BlowfishStream::new(key.as_slice(), iv).unwrap();
+            let mut dec = BlowfishStream::new(key.as_slice(), iv).unwrap();
+
+            let mut data = pt.clone();
+            for packet in data.chunks_mut(packet_size) {
+                enc.encrypt(packet).unwrap();
+            }
+            assert_eq!(data.as_slice(), ct.as_slice());
+
+            for packet in data.chunks_mut(packet_size) {
+                dec.decrypt(packet).unwrap();
+            }
+            assert_eq!(data.as_slice(), pt.as_slice());
+        }
+
+        // reset restarts the stream
+        let mut enc = BlowfishStream::new(key.as_slice(), iv).unwrap();
+        let mut data = pt.clone();
+        enc.encrypt(&mut data[..7]).unwrap();
+        enc.reset(iv);
+        let mut data = pt.clone();
+        enc.encrypt(&mut data).unwrap();
+        assert_eq!(data.as_slice(), ct.as_slice());
+    }
+
+    #[test]
+    pub fn test_bf_bad_key() {
+        for key_len in [0, 3, 57, 100] {
+            let key = vec![0x5a_u8; key_len];
+            assert!(matches!(
+                Blowfish::encrypt(key.as_slice(), 0, b"data"),
+                Err(CryptoError::BadKey(_))
+            ));
+            assert!(matches!(
+                Blowfish::decrypt(key.as_slice(), 0, b"data"),
+                Err(CryptoError::BadKey(_))
+            ));
+            assert!(matches!(
+                BlowfishStream::new(key.as_slice(), 0),
+                Err(CryptoError::BadKey(_))
+            ));
+        }
+
+        for key_len in [4, 56] {
+            let key = vec![0x5a_u8; key_len];
+            let ct = Blowfish::encrypt(key.as_slice(), 0, b"data").unwrap();
+            let pt = Blowfish::decrypt(key.as_slice(), 0, ct.as_slice()).unwrap();
+            assert_eq!(pt.as_slice(), b"data");
+        }
+    }
+
+    #[test]
+    pub fn test_bf_cxx_errors() {
+        use commlib_sys::crypto::{
+            blowfish_encrypt, blowfish_encrypt_in_place, blowfish_set_key, new_blowfish,
+        };
+
+        // checked on the C++ side as well
+        let cipher = new_blowfish().unwrap();
+        assert!(blowfish_set_key(cipher.clone(), b"abc").is_err());
+        let err = blowfish_set_key(cipher.clone(), &[0x5a_u8; 57]).unwrap_err();
+        assert!(err.what().ends_with("out of range [4, 56]"));
+
+        // not keyed yet
+        let mut data = [0_u8; 8];
+        assert!(blowfish_encrypt(cipher.clone(), &data).is_err());
+        assert!(blowfish_encrypt_in_place(cipher.clone(), &mut data).is_err());
+
+        let err: CryptoError = blowfish_encrypt(cipher, &data).unwrap_err().into();
+        assert!(matches!(err, CryptoError::Internal(_)));
+    }
 }
@@ -0,0 +1,53 @@
+//!
+//! Commlib: CRC32
+//!
+
+use commlib_sys::ffi_hash::crc32;
+
+pub struct Crc32();
+
+impl Crc32 {
+    /// CRC32 as 8 hex characters
+    #[inline(always)]
+    pub fn hash_slice(data: &[u8]) -> String {
+        crc32(data)
+    }
+
+    /// CRC32 as 8 hex characters
+    #[inline(always)]
+    pub fn hash(data: &str) -> String {
+        Self::hash_slice(data.as_bytes())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use hex::{self, FromHex};
+
+    use commlib_sys::ffi_hash::crc32_hash_bytes;
+
+    use crate::utils::Crc32;
+
+    #[test]
+    fn test_crc32() {
+        // CRC-32/ISO-HDLC check values
+        const CRC32_TESTS: [(&str, &str); 4] = [
+            ("", "00000000"),
+            ("313233343536373839", "cbf43926"),
+            ("7F", "12b88320"),
+            (
+                "54686520717569636b2062726f776e20666f78206a756d7073206f76657220746865206c617a7920646f67",
+                "414fa339",
+            ),
+        ];
+
+        for test in CRC32_TESTS.iter() {
+            // treat string as hex bytes, such as "7F" means 0x7F
+            let digest = Crc32::hash_slice(Vec::from_hex(test.0).unwrap().as_slice());
+            assert_eq!(digest, test.1);
+        }
+
+        assert_eq!(Crc32::hash("123456789"), "cbf43926");
+        assert_eq!(crc32_hash_bytes(), 4);
+    }
+}
@@ -0,0 +1,31 @@
+//!
+//! Commlib: CryptoError
+//!
+
+use std::fmt;
+
+/// Errors of the crypto helpers in [`crate::utils`]
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum CryptoError {
+    /// The key is rejected by the algorithm, e.g. wrong length
+    BadKey(String),
+    /// Failure inside the underlying implementation
+    Internal(String),
+}
+
+impl fmt::Display for CryptoError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            CryptoError::BadKey(msg) => write!(f, "bad key: {}", msg),
+            CryptoError::Internal(msg) => write!(f, "internal error: {}", msg),
+        }
+    }
+}
+
+impl std::error::Error for CryptoError {}
+
+impl From<cxx::Exception> for CryptoError {
+    fn from(e: cxx::Exception) -> Self {
+        CryptoError::Internal(e.what().to_owned())
+    }
+}
@@ -0,0 +1,151 @@
+//!
+//! Commlib: Hasher -- incremental (streaming) hashing
+//!
+
+use cxx::SharedPtr;
+
+use commlib_sys::hash::{
+    hasher_finalize_bytes, hasher_finalize_hex, hasher_reset, hasher_update, new_crc32_hasher,
+    new_keccak_hasher, new_md5_hasher, new_sha1_hasher, new_sha256_hasher, new_sha3_hasher,
+    Hasher as CxxHasher,
+};
+
+use super::Sha3Bits;
+
+/// Incremental hasher over the C++ hash implementations
+///
+/// Feed data chunk by chunk with [`update`](Hasher::update) (or pipe it through
+/// [`std::io::copy`]), then read the digest with [`finalize_hex`](Hasher::finalize_hex)
+/// or [`finalize_bytes`](Hasher::finalize_bytes). Finalizing does not consume the state,
+/// so more data can be appended afterwards; call [`reset`](Hasher::reset) to start over.
+pub struct Hasher {
+    inner: SharedPtr<CxxHasher>,
+}
+
+// The C++ hasher is only reachable through this wrapper, which never shares its pointer.
+unsafe impl Send for Hasher {}
+
+impl Hasher {
+    /// MD5 hasher
+    pub fn md5() -> Self {
+        Self {
+            inner: new_md5_hasher(),
+        }
+    }
+
+    /// SHA1 hasher
+    pub fn sha1() -> Self {
+        Self {
+            inner: new_sha1_hasher(),
+        }
+    }
+
+    /// SHA256 hasher
+    pub fn sha256() -> Self {
+        Self {
+            inner: new_sha256_hasher(),
+        }
+    }
+
+    /// SHA3 hasher
+    pub fn sha3(bits: Sha3Bits) -> Self {
+        Self {
+            inner: new_sha3_hasher(bits as u32),
+        }
+    }
+
+    /// Keccak hasher
+    pub fn keccak(bits: Sha3Bits) -> Self {
+        Self {
+            inner: new_keccak_hasher(bits as u32),
+        }
+    }
+
+    /// CRC32 hasher
+    pub fn crc32() -> Self {
+        Self {
+            inner: new_crc32_hasher(),
+        }
+    }
+
+    /// Append a chunk of data
+    #[inline(always)]
+    pub fn update(&mut self, data: &[u8]) {
+        hasher_update(self.inner.clone(), data);
+    }
+
+    /// Digest of all data so far, as lowercase hex characters
+    #[inline(always)]
+    pub fn finalize_hex(&mut self) -> String {
+        hasher_finalize_hex(self.inner.clone())
+    }
+
+    /// Digest of all data so far, as raw bytes
+    #[inline(always)]
+    pub fn finalize_bytes(&mut self) -> Vec<u8> {
+        hasher_finalize_bytes(self.inner.clone())
+    }
+
+    /// Restart
+    #[inline(always)]
+    pub fn reset(&mut self) {
+        hasher_reset(self.inner.clone());
+    }
+}
+
+impl std::io::Write for Hasher {
+    #[inline(always)]
+    fn write(&mut self, buf: &[u8]) -> std::io::Result<usize> {
+        self.update(buf);
+        Ok(buf.len())
+    }
+
+    #[inline(always)]
+    fn flush(&mut self) -> std::io::Result<()> {
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::utils::{Crc32, Hasher, Keccak, Md5, Sha1, Sha256, Sha3, Sha3Bits};
+
+    #[test]
+    fn test_hasher_chunks() {
+        let data: Vec<u8> = (0..10_000_u32).map(|i| (i % 251) as u8).collect();
+
+        let cases: Vec<(Hasher, String)> = vec![
+            (Hasher::md5(), Md5::hash_slice(&data)),
+            (Hasher::sha1(), Sha1::hash_slice(&data)),
+            (Hasher::sha256(), Sha256::hash_slice(&data)),
+            (
+                Hasher::sha3(Sha3Bits::Bits512),
+                Sha3::hash_slice_with_bits(&data, Sha3Bits::Bits512),
+            ),
+            (Hasher::keccak(Sha3Bits::Bits256), Keccak::hash_slice(&data)),
+            (Hasher::crc32(), Crc32::hash_slice(&data)),
+        ];
+
+        for (mut hasher, expected) in cases {
+            for chunk in data.chunks(333) {
+                hasher.update(chunk);
+            }
+            assert_eq!(hasher.finalize_hex(), expected);
+            assert_eq!(hex::encode(hasher.finalize_bytes()), expected);
+
+            hasher.reset();
+            hasher.update(b"abc");
+            assert_eq!(hex::encode(hasher.finalize_bytes()), hasher.finalize_hex());
+        }
+    }
+
+    #[test]
+    fn test_hasher_io_copy() {
+        let data = "The quick brown fox jumps over the lazy dog".repeat(1000);
+
+        let mut hasher = Hasher::sha256();
+        let n = std::io::copy(&mut data.as_bytes(), &mut hasher).unwrap();
+        assert_eq!(n as usize, data.len());
+        assert_eq!(hasher.finalize_hex(), Sha256::hash(&data));
+    }
+}
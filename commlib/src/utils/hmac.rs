@@ -0,0 +1,198 @@
+//!
+//! Commlib: HMAC
+//!
+
+use commlib_sys::hash::{hmac_md5, hmac_sha1, hmac_sha256};
+
+/// HMAC 摘要算法
+#[derive(Debug, PartialEq, Eq, Copy, Clone)]
+pub enum HmacAlgorithm {
+    Md5,
+    Sha1,
+    Sha256,
+}
+
+pub struct Hmac();
+
+impl Hmac {
+    /// HMAC-MD5 as hex characters
+    #[inline(always)]
+    pub fn md5(key: &[u8], data: &[u8]) -> String {
+        hmac_md5(data, key)
+    }
+
+    /// HMAC-SHA1 as hex characters
+    #[inline(always)]
+    pub fn sha1(key: &[u8], data: &[u8]) -> String {
+        hmac_sha1(data, key)
+    }
+
+    /// HMAC-SHA256 as hex characters
+    #[inline(always)]
+    pub fn sha256(key: &[u8], data: &[u8]) -> String {
+        hmac_sha256(data, key)
+    }
+
+    /// HMAC as hex characters
+    #[inline(always)]
+    pub fn sign(algorithm: HmacAlgorithm, key: &[u8], data: &[u8]) -> String {
+        match algorithm {
+            HmacAlgorithm::Md5 => Self::md5(key, data),
+            HmacAlgorithm::Sha1 => Self::sha1(key, data),
+            HmacAlgorithm::Sha256 => Self::sha256(key, data),
+        }
+    }
+
+    /// 校验 hex 签名（大小写均可），比较耗时与签名内容无关
+    pub fn verify(algorithm: HmacAlgorithm, key: &[u8], data: &[u8], signature: &str) -> bool {
+        let expected = hex::decode(Self::sign(algorithm, key, data)).unwrap_or_default();
+        match hex::decode(signature) {
+            Ok(actual) => constant_time_eq(&expected, &actual),
+            Err(_) => false,
+        }
+    }
+}
+
+#[inline(always)]
+fn constant_time_eq(a: &[u8], b: &[u8]) -> bool {
+    if a.len() != b.len() {
+        return false;
+    }
+
+    let diff = a
+        .iter()
+        .zip(b.iter())
+        .fold(0_u8, |acc, (x, y)| acc | (x ^ y));
+    std::hint::black_box(diff) == 0
+}
+
+#[cfg(test)]
+mod tests {
+    use hex::{self, FromHex};
+
+    use crate::utils::{Hmac, HmacAlgorithm};
+
+    #[test]
+    fn test_hmac() {
+        // Test vectors from RFC 2202 (MD5, SHA1) and RFC 4231 (SHA256): cases 1, 2, 3, 6
+        const HMAC_TESTS: [(HmacAlgorithm, &str, &str, &str); 12] = [
+            (
+                HmacAlgorithm::Md5,
+                "0b0b0b0b0b0b0b0b0b0b0b0b0b0b0b0b",
+                "4869205468657265",
+                "9294727a3638bb1c13f48ef8158bfc9d",
+            ),
+            (
+                HmacAlgorithm::Md5,
+                "4a656665",
+                "7768617420646f2079612077616e7420666f72206e6f7468696e673f",
+                "750c783e6ab0b503eaa86e310a5db738",
+            ),
+            (
+                HmacAlgorithm::Md5,
+                "aaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaa",
+                "dddddddddddddddddddddddddddddddddddddddddddddddddddddddddddddddddddddddddddddddddddddddddddddddddddd",
+                "56be34521d144c88dbb8c733f0e8b3f6",
+            ),
+            (
+                HmacAlgorithm::Md5,
+                "aaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaa",
+                "54657374205573696e67204c6172676572205468616e20426c6f636b2d53697a65204b6579202d2048617368204b6579204669727374",
+                "6b1ab7fe4bd7bf8f0b62e6ce61b9d0cd",
+            ),
+            (
+                HmacAlgorithm::Sha1,
+                "0b0b0b0b0b0b0b0b0b0b0b0b0b0b0b0b0b0b0b0b",
+                "4869205468657265",
+                "b617318655057264e28bc0b6fb378c8ef146be00",
+            ),
+            (
+                HmacAlgorithm::Sha1,
+                "4a656665",
+                "7768617420646f2079612077616e7420666f72206e6f7468696e673f",
+                "effcdf6ae5eb2fa2d27416d5f184df9c259a7c79",
+            ),
+            (
+                HmacAlgorithm::Sha1,
+                "aaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaa",
+                "dddddddddddddddddddddddddddddddddddddddddddddddddddddddddddddddddddddddddddddddddddddddddddddddddddd",
+                "125d7342b9ac11cd91a39af48aa17b4f63f175d3",
+            ),
+            (
+                HmacAlgorithm::Sha1,
+                "aaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaa",
+                "54657374205573696e67204c6172676572205468616e20426c6f636b2d53697a65204b6579202d2048617368204b6579204669727374",
+                "aa4ae5e15272d00e95705637ce8a3b55ed402112",
+            ),
+            (
+                HmacAlgorithm::Sha256,
+                "0b0b0b0b0b0b0b0b0b0b0b0b0b0b0b0b0b0b0b0b",
+                "4869205468657265",
+                "b0344c61d8db38535ca8afceaf0bf12b881dc200c9833da726e9376c2e32cff7",
+            ),
+            (
+                HmacAlgorithm::Sha256,
+                "4a656665",
+                "7768617420646f2079612077616e7420666f72206e6f7468696e673f",
+                "5bdcc146bf60754e6a042426089575c75a003f089d2739839dec58b964ec3843",
+            ),
+            (
+                HmacAlgorithm::Sha256,
+                "aaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaa",
+                "dddddddddddddddddddddddddddddddddddddddddddddddddddddddddddddddddddddddddddddddddddddddddddddddddddd",
+                "773ea91e36800e46854db8ebd09181a72959098b3ef8c122d9635514ced565fe",
+            ),
+            (
+                HmacAlgorithm::Sha256,
+                "aaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaa",
+                "54657374205573696e67204c6172676572205468616e20426c6f636b2d53697a65204b6579202d2048617368204b6579204669727374",
+                "60e431591ee0b67f0d8a26aacbf5b77f8e0bc6213728c5140546040f0ee37f54",
+            ),
+        ];
+
+        for test in HMAC_TESTS.iter() {
+            // treat string as hex bytes, such as "7F" means 0x7F
+            let key = Vec::from_hex(test.1).unwrap();
+            let data = Vec::from_hex(test.2).unwrap();
+            assert_eq!(Hmac::sign(test.0, &key, &data), test.3);
+            assert!(Hmac::verify(test.0, &key, &data, test.3));
+            assert!(Hmac::verify(test.0, &key, &data, &test.3.to_uppercase()));
+        }
+    }
+
+    #[test]
+    fn test_hmac_verify_mismatch() {
+        let sig = Hmac::sha256(b"Jefe", b"what do ya want for nothing?");
+
+        assert!(!Hmac::verify(
+            HmacAlgorithm::Sha256,
+            b"Jefe",
+            b"what do ya want for something?",
+            &sig
+        ));
+        assert!(!Hmac::verify(
+            HmacAlgorithm::Sha256,
+            b"jefe",
+            b"what do ya want for nothing?",
+            &sig
+        ));
+        assert!(!Hmac::verify(
+            HmacAlgorithm::Sha256,
+            b"Jefe",
+            b"what do ya want for nothing?",
+            &sig[..62]
+        ));
+        assert!(!Hmac::verify(
+            HmacAlgorithm::Sha256,
+            b"Jefe",
+            b"what do ya want for nothing?",
+            "not hex"
+        ));
+        assert!(!Hmac::verify(
+            HmacAlgorithm::Sha1,
+            b"Jefe",
+            b"what do ya want for nothing?",
+            &sig
+        ));
+    }
+}
@@ -0,0 +1,76 @@
+//!
+//! Commlib: Keccak
+//!
+
+use commlib_sys::ffi_hash::keccak;
+
+use super::Sha3Bits;
+
+/// Keccak (the original SHA3 submission, as used by Ethereum)
+pub struct Keccak();
+
+impl Keccak {
+    /// Keccak-256
+    #[inline(always)]
+    pub fn hash_slice(data: &[u8]) -> String {
+        Self::hash_slice_with_bits(data, Sha3Bits::default())
+    }
+
+    /// Keccak-256
+    #[inline(always)]
+    pub fn hash(data: &str) -> String {
+        Self::hash_slice(data.as_bytes())
+    }
+
+    /// Hex digest of a byte slice with the given digest size
+    #[inline(always)]
+    pub fn hash_slice_with_bits(data: &[u8], bits: Sha3Bits) -> String {
+        keccak(data, bits as u32)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use hex::{self, FromHex};
+
+    use crate::utils::{Keccak, Sha3Bits};
+
+    #[test]
+    fn test_keccak() {
+        // Known answers of the Keccak[c=2d] submission (pre-FIPS 202 padding)
+        const KECCAK_TESTS: [(&str, Sha3Bits, &str); 4] = [
+            (
+                "",
+                Sha3Bits::Bits224,
+                "f71837502ba8e10837bdd8d365adb85591895602fc552b48b7390abd",
+            ),
+            (
+                "",
+                Sha3Bits::Bits256,
+                "c5d2460186f7233c927e7db2dcc703c0e500b653ca82273b7bfad8045d85a470",
+            ),
+            (
+                "616263",
+                Sha3Bits::Bits256,
+                "4e03657aea45a94fc7d47ba826c8d667c0d1e6e33a64a036ec44f58fa12d6c45",
+            ),
+            (
+                "",
+                Sha3Bits::Bits512,
+                "0eab42de4c3ceb9235fc91acffe746b29c29a8c366b7c60e4e67c466f36a4304c00fa9caf9d87976ba469bcbe06713b435f091ef2769fb160cdab33d3670680e",
+            ),
+        ];
+
+        for test in KECCAK_TESTS.iter() {
+            // treat string as hex bytes, such as "7F" means 0x7F
+            let digest =
+                Keccak::hash_slice_with_bits(Vec::from_hex(test.0).unwrap().as_slice(), test.1);
+            assert_eq!(digest, test.2);
+        }
+
+        assert_eq!(
+            Keccak::hash("abc"),
+            "4e03657aea45a94fc7d47ba826c8d667c0d1e6e33a64a036ec44f58fa12d6c45"
+        );
+    }
+}
@@ -1,15 +1,19 @@
 use ring::rand::{SecureRandom, SystemRandom};
 
+use super::CryptoError;
+
 ///
 #[inline(always)]
-pub fn rand_bytes(buf: &mut [u8]) -> Result<(), String> {
-    rng().fill(buf).map_err(|e| e.to_string())?;
+pub fn rand_bytes(buf: &mut [u8]) -> Result<(), CryptoError> {
+    rng()
+        .fill(buf)
+        .map_err(|e| CryptoError::Internal(e.to_string()))?;
     Ok(())
 }
 
 ///
 #[inline(always)]
-pub fn rand_bytes2(size: usize) -> Result<Vec<u8>, String> {
+pub fn rand_bytes2(size: usize) -> Result<Vec<u8>, CryptoError> {
     let mut buf: Vec<u8> = vec![0; size];
     match rand_bytes(buf.as_mut_slice()) {
         Ok(_) => Ok(buf),
@@ -45,7 +49,7 @@ mod tests {
     }
 
     fn write_bytes<W: Write>(size: usize, writer: &mut W) -> Result<(), String> {
-        let bytes = rand_bytes2(size)?;
+        let bytes = rand_bytes2(size).map_err(|e| e.to_string())?;
         writer.write_all(&bytes).map_err(|e| e.to_string())?;
         Ok(())
     }
@@ -0,0 +1,57 @@
+//!
+//! Commlib: SHA1
+//!
+
+use commlib_sys::ffi_hash::sha1;
+
+pub struct Sha1();
+
+impl Sha1 {
+    /// Hex digest of a byte slice
+    #[inline(always)]
+    pub fn hash_slice(data: &[u8]) -> String {
+        sha1(data)
+    }
+
+    /// Hex digest of a string
+    #[inline(always)]
+    pub fn hash(data: &str) -> String {
+        Self::hash_slice(data.as_bytes())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use hex::{self, FromHex};
+
+    use commlib_sys::ffi_hash::{sha1_block_size, sha1_hash_bytes};
+
+    use crate::utils::Sha1;
+
+    #[test]
+    fn test_sha1() {
+        // Test vectors from FIPS 180-2 and https://www.di-mgt.com.au/sha_testvectors.html
+        const SHA1_TESTS: [(&str, &str); 4] = [
+            ("", "da39a3ee5e6b4b0d3255bfef95601890afd80709"),
+            ("616263", "a9993e364706816aba3e25717850c26c9cd0d89d"),
+            (
+                "6162636462636465636465666465666765666768666768696768696a68696a6b696a6b6c6a6b6c6d6b6c6d6e6c6d6e6f6d6e6f706e6f7071",
+                "84983e441c3bd26ebaae4aa1f95129e5e54670f1",
+            ),
+            ("7F", "23833462f55515a900e016db2eb943fb474c19f6"),
+        ];
+
+        for test in SHA1_TESTS.iter() {
+            // treat string as hex bytes, such as "7F" means 0x7F
+            let digest = Sha1::hash_slice(Vec::from_hex(test.0).unwrap().as_slice());
+            assert_eq!(digest, test.1);
+        }
+
+        assert_eq!(
+            Sha1::hash("abc"),
+            "a9993e364706816aba3e25717850c26c9cd0d89d"
+        );
+        assert_eq!(sha1_block_size(), 64);
+        assert_eq!(sha1_hash_bytes(), 20);
+    }
+}
@@ -0,0 +1,66 @@
+//!
+//! Commlib: SHA256
+//!
+
+use commlib_sys::ffi_hash::sha256;
+
+pub struct Sha256();
+
+impl Sha256 {
+    /// Hex digest of a byte slice
+    #[inline(always)]
+    pub fn hash_slice(data: &[u8]) -> String {
+        sha256(data)
+    }
+
+    /// Hex digest of a string
+    #[inline(always)]
+    pub fn hash(data: &str) -> String {
+        Self::hash_slice(data.as_bytes())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use hex::{self, FromHex};
+
+    use commlib_sys::ffi_hash::{sha256_block_size, sha256_hash_bytes};
+
+    use crate::utils::Sha256;
+
+    #[test]
+    fn test_sha256() {
+        // Test vectors from FIPS 180-2 and https://www.di-mgt.com.au/sha_testvectors.html
+        const SHA256_TESTS: [(&str, &str); 4] = [
+            (
+                "",
+                "e3b0c44298fc1c149afbf4c8996fb92427ae41e4649b934ca495991b7852b855",
+            ),
+            (
+                "616263",
+                "ba7816bf8f01cfea414140de5dae2223b00361a396177a9cb410ff61f20015ad",
+            ),
+            (
+                "6162636462636465636465666465666765666768666768696768696a68696a6b696a6b6c6a6b6c6d6b6c6d6e6c6d6e6f6d6e6f706e6f7071",
+                "248d6a61d20638b8e5c026930c3e6039a33ce45964ff2167f6ecedd419db06c1",
+            ),
+            (
+                "7F",
+                "620bfdaa346b088fb49998d92f19a7eaf6bfc2fb0aee015753966da1028cb731",
+            ),
+        ];
+
+        for test in SHA256_TESTS.iter() {
+            // treat string as hex bytes, such as "7F" means 0x7F
+            let digest = Sha256::hash_slice(Vec::from_hex(test.0).unwrap().as_slice());
+            assert_eq!(digest, test.1);
+        }
+
+        assert_eq!(
+            Sha256::hash("abc"),
+            "ba7816bf8f01cfea414140de5dae2223b00361a396177a9cb410ff61f20015ad"
+        );
+        assert_eq!(sha256_block_size(), 64);
+        assert_eq!(sha256_hash_bytes(), 32);
+    }
+}
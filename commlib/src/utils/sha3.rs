@@ -0,0 +1,94 @@
+//!
+//! Commlib: SHA3
+//!
+
+use commlib_sys::ffi_hash::sha3;
+
+/// SHA3 / Keccak 摘要位数
+#[derive(Debug, Default, PartialEq, Eq, Copy, Clone)]
+#[repr(u32)]
+pub enum Sha3Bits {
+    Bits224 = 224,
+    #[default]
+    Bits256 = 256,
+    Bits384 = 384,
+    Bits512 = 512,
+}
+
+pub struct Sha3();
+
+impl Sha3 {
+    /// SHA3-256
+    #[inline(always)]
+    pub fn hash_slice(data: &[u8]) -> String {
+        Self::hash_slice_with_bits(data, Sha3Bits::default())
+    }
+
+    /// SHA3-256
+    #[inline(always)]
+    pub fn hash(data: &str) -> String {
+        Self::hash_slice(data.as_bytes())
+    }
+
+    /// Hex digest of a byte slice with the given digest size
+    #[inline(always)]
+    pub fn hash_slice_with_bits(data: &[u8], bits: Sha3Bits) -> String {
+        sha3(data, bits as u32)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use hex::{self, FromHex};
+
+    use crate::utils::{Sha3, Sha3Bits};
+
+    #[test]
+    fn test_sha3() {
+        // Test vectors from https://www.di-mgt.com.au/sha_testvectors.html
+        const SHA3_TESTS: [(&str, Sha3Bits, &str); 6] = [
+            (
+                "",
+                Sha3Bits::Bits256,
+                "a7ffc6f8bf1ed76651c14756a061d662f580ff4de43b49fa82d80a4b80f8434a",
+            ),
+            (
+                "616263",
+                Sha3Bits::Bits224,
+                "e642824c3f8cf24ad09234ee7d3c766fc9a3a5168d0c94ad73b46fdf",
+            ),
+            (
+                "616263",
+                Sha3Bits::Bits256,
+                "3a985da74fe225b2045c172d6bd390bd855f086e3e9d525b46bfe24511431532",
+            ),
+            (
+                "616263",
+                Sha3Bits::Bits384,
+                "ec01498288516fc926459f58e2c6ad8df9b473cb0fc08c2596da7cf0e49be4b298d88cea927ac7f539f1edf228376d25",
+            ),
+            (
+                "616263",
+                Sha3Bits::Bits512,
+                "b751850b1a57168a5693cd924b6b096e08f621827444f70d884f5d0240d2712e10e116e9192af3c91a7ec57647e3934057340b4cf408d5a56592f8274eec53f0",
+            ),
+            (
+                "6162636462636465636465666465666765666768666768696768696a68696a6b696a6b6c6a6b6c6d6b6c6d6e6c6d6e6f6d6e6f706e6f7071",
+                Sha3Bits::Bits256,
+                "41c0dba2a9d6240849100376a8235e2c82e1b9998a999e21db32dd97496d3376",
+            ),
+        ];
+
+        for test in SHA3_TESTS.iter() {
+            // treat string as hex bytes, such as "7F" means 0x7F
+            let digest =
+                Sha3::hash_slice_with_bits(Vec::from_hex(test.0).unwrap().as_slice(), test.1);
+            assert_eq!(digest, test.2);
+        }
+
+        assert_eq!(
+            Sha3::hash("abc"),
+            "3a985da74fe225b2045c172d6bd390bd855f086e3e9d525b46bfe24511431532"
+        );
+    }
+}
@@ -10,8 +10,8 @@
 
 //! A thread pool used to execute functions in parallel.
 //!
-//! Spawns a specified number of worker threads and replenishes the pool if any worker threads
-//! panic.
+//! Spawns a specified number of worker threads. A panicking job is caught and reported to the
+//! [`panic_handler`](Builder::panic_handler), the thread goes on with the next job of its queue.
 //!
 //! # Examples
 //!
@@ -77,12 +77,87 @@
 //! barrier.wait();
 //! assert_eq!(an_atomic.load(Ordering::SeqCst), /* n_jobs = */ 23);
 //! ```
+//!
+//! ## Work stealing
+//!
+//! By default every job is pinned to one thread, and `execute_rr` picks the thread blindly,
+//! so a slow job holds back the jobs queued behind it. With
+//! [`work_stealing`](Builder::work_stealing) enabled, `execute_rr` jobs go into a global
+//! injector; each thread takes batches of them into its own deque and idle threads steal from
+//! the busy ones. `execute(pos, ..)` keeps its strict affinity, jobs with the same `pos` still
+//! run one by one in order.
+//!
+//! ```rust, no_run
+//! use commlib::utils::ThreadPoolBuilder;
+//!
+//! let pool = ThreadPoolBuilder::new()
+//!     .num_threads(4)
+//!     .work_stealing(true)
+//!     .build();
+//!
+//! // any thread
+//! pool.execute_rr(|| println!("hello"));
+//!
+//! // always the same thread, in order
+//! let player_id = 42;
+//! pool.execute(player_id, || println!("world"));
+//! pool.join();
+//! ```
+//!
+//! ## Results and scoped jobs
+//!
+//! [`submit`](ThreadPool::submit) returns a [`JobHandle`] to wait for (or `.await`) the result,
+//! and [`scope`](ThreadPool::scope) runs jobs which borrow from the caller's stack.
+//!
+//! ```rust, no_run
+//! use commlib::utils::ThreadPool;
+//!
+//! let pool = ThreadPool::new(4);
+//!
+//! let handle = pool.submit(0, || 6 * 7);
+//! assert_eq!(42, handle.join().unwrap());
+//!
+//! let mut numbers = vec![1, 2, 3, 4, 5, 6, 7, 8];
+//! pool.scope(|s| {
+//!     for (pos, chunk) in numbers.chunks_mut(2).enumerate() {
+//!         s.execute(pos, move || chunk.iter_mut().for_each(|n| *n *= 2));
+//!     }
+//! });
+//! assert_eq!(numbers, vec![2, 4, 6, 8, 10, 12, 14, 16]);
+//! ```
+//!
+//! ## Shutdown
+//!
+//! The threads stop when the last clone of the pool is dropped, or on
+//! [`shutdown`](ThreadPool::shutdown), which waits for the queued jobs to finish (or drops them)
+//! and reports what was left over at the deadline.
+//!
+//! ```rust, no_run
+//! use commlib::utils::{DrainPolicy, ThreadPool};
+//! use std::time::Duration;
+//!
+//! let pool = ThreadPool::new(4);
+//! pool.execute(0, || println!("save player"));
+//!
+//! let report = pool.shutdown(DrainPolicy::Finish, Duration::from_secs(30));
+//! assert!(report.is_clean());
+//! ```
 
 use crossbeam_channel as channel;
+use crossbeam_deque as deque;
 use parking_lot::{Condvar, Mutex};
+use std::any::Any;
+use std::borrow::Cow;
+use std::cell::Cell;
 use std::fmt;
-use std::sync::atomic::{AtomicUsize, Ordering};
+use std::future::Future;
+use std::marker::PhantomData;
+use std::panic::{self, AssertUnwindSafe};
+use std::pin::Pin;
+use std::sync::atomic::{AtomicBool, AtomicUsize, Ordering};
 use std::sync::Arc;
+use std::task::{Context, Poll, Waker};
+use std::time::{Duration, Instant};
 
 use std::thread;
 
@@ -98,10 +173,30 @@ impl<F: FnOnce()> FnBox for F {
 
 type Thunk<'a> = Box<dyn FnBox + Send + 'a>;
 
+/// Called with `(thread_index, payload, job_label)` when a job panics, see
+/// [`Builder::panic_handler`].
+pub type PanicHandler = Arc<dyn Fn(usize, &(dyn Any + Send), Option<&str>) + Send + Sync>;
+
+thread_local! {
+    // index of the pool thread running on this thread
+    static THREAD_INDEX: Cell<usize> = const { Cell::new(0) };
+}
+
+/// Panic message of a payload from `panic!`, if any.
+pub fn panic_message(payload: &(dyn Any + Send)) -> Option<&str> {
+    if let Some(s) = payload.downcast_ref::<&'static str>() {
+        Some(s)
+    } else {
+        payload.downcast_ref::<String>().map(String::as_str)
+    }
+}
+
 struct Sentinel {
     shared_data: Arc<ThreadPoolSharedData>,
     rx: channel::Receiver<Thunk<'static>>,
     rx_index: usize,
+    // work-stealing deque of this thread, handed over to the replacement thread
+    local: Option<deque::Worker<Thunk<'static>>>,
     active: bool,
 }
 
@@ -110,15 +205,57 @@ impl Sentinel {
         shared_data: &Arc<ThreadPoolSharedData>,
         rx: channel::Receiver<Thunk<'static>>,
         rx_index: usize,
+        local: Option<deque::Worker<Thunk<'static>>>,
     ) -> Sentinel {
         Sentinel {
             shared_data: shared_data.clone(),
             rx,
             rx_index,
+            local,
             active: true,
         }
     }
 
+    /// Next job of this thread: its own channel first, then the work-stealing queues.
+    ///
+    /// Returns `None` once the pool was dropped or shut down, and there is nothing left to run.
+    fn next_job(&self) -> Option<Thunk<'static>> {
+        let stop_rx = &self.shared_data.stop_rx;
+        let (stealing, local) = match (&self.shared_data.stealing, &self.local) {
+            (Some(stealing), Some(local)) => (stealing, local),
+            _ => {
+                // the queue is empty by the time the pool stops
+                return channel::select! {
+                    recv(self.rx) -> msg => msg.ok().map(|job| self.own_job(job)),
+                    recv(stop_rx) -> _ => None,
+                };
+            }
+        };
+
+        loop {
+            if let Ok(job) = self.rx.try_recv() {
+                return Some(self.own_job(job));
+            }
+            if let Some(job) = stealing.find_job(local, self.rx_index - 1) {
+                stealing.slot.release();
+                return Some(job);
+            }
+
+            channel::select! {
+                recv(self.rx) -> msg => return msg.ok().map(|job| self.own_job(job)),
+                recv(stealing.wake_rx) -> _ => continue,
+                recv(stop_rx) -> _ => return None,
+            }
+        }
+    }
+
+    /// A job taken from this thread's channel leaves room in its slot queue.
+    #[inline(always)]
+    fn own_job(&self, job: Thunk<'static>) -> Thunk<'static> {
+        self.shared_data.slots[self.rx_index - 1].release();
+        job
+    }
+
     /// Cancel and destroy this sentinel.
     fn cancel(mut self) {
         self.active = false;
@@ -133,7 +270,12 @@ impl Drop for Sentinel {
                 self.shared_data.panic_count.fetch_add(1, Ordering::SeqCst);
             }
             self.shared_data.no_work_notify_all();
-            spawn_in_pool(self.shared_data.clone(), self.rx.clone(), self.rx_index);
+            spawn_in_pool(
+                self.shared_data.clone(),
+                self.rx.clone(),
+                self.rx_index,
+                self.local.take(),
+            );
         }
     }
 }
@@ -141,13 +283,16 @@ impl Drop for Sentinel {
 /// [`ThreadPool`] factory, which can be used in order to configure the properties of the
 /// [`ThreadPool`].
 ///
-/// The three configuration options available:
+/// The six configuration options available:
 ///
 /// * `num_threads`: maximum number of threads that will be alive at any given moment by the built
 ///   [`ThreadPool`]
 /// * `thread_name`: thread name for each of the threads spawned by the built [`ThreadPool`]
 /// * `thread_stack_size`: stack size (in bytes) for each of the threads spawned by the built
 ///   [`ThreadPool`]
+/// * `work_stealing`: schedule `execute_rr` jobs with a global injector and per-thread deques
+/// * `queue_capacity`: maximum number of jobs waiting in the queue of each thread
+/// * `panic_handler`: called when a job panics
 ///
 /// [`ThreadPool`]: struct.ThreadPool.html
 ///
@@ -167,6 +312,9 @@ pub struct Builder {
     num_threads: Option<usize>,
     thread_name: Option<String>,
     thread_stack_size: Option<usize>,
+    work_stealing: bool,
+    queue_capacity: Option<usize>,
+    panic_handler: Option<PanicHandler>,
 }
 
 impl Builder {
@@ -184,6 +332,9 @@ impl Builder {
             num_threads: None,
             thread_name: None,
             thread_stack_size: None,
+            work_stealing: false,
+            queue_capacity: None,
+            panic_handler: None,
         }
     }
 
@@ -273,6 +424,103 @@ impl Builder {
         self
     }
 
+    /// Enable the work-stealing scheduler for [`ThreadPool::execute_rr`]: jobs go into a global
+    /// injector instead of a fixed thread, each thread takes batches of them into its own deque
+    /// and idle threads steal from busy ones. Disabled by default.
+    ///
+    /// [`ThreadPool::execute`] is not affected, jobs with the same `pos` always run on the same
+    /// thread in order.
+    ///
+    /// # Examples
+    ///
+    /// A slow job doesn't hold back the jobs submitted after it:
+    ///
+    /// ```rust, no_run
+    /// use std::thread::sleep;
+    /// use std::time::Duration;
+    ///
+    /// let pool = commlib::utils::ThreadPoolBuilder::new()
+    ///     .num_threads(2)
+    ///     .work_stealing(true)
+    ///     .build();
+    ///
+    /// pool.execute_rr(|| sleep(Duration::from_secs(10)));
+    /// for _ in 0..100 {
+    ///     pool.execute_rr(|| println!("not waiting for the slow job"));
+    /// }
+    /// ```
+    pub fn work_stealing(mut self, enable: bool) -> Builder {
+        self.work_stealing = enable;
+        self
+    }
+
+    /// Set the maximum number of jobs waiting in the queue of each thread of the built
+    /// [`ThreadPool`]. If not specified, the queues are unbounded.
+    ///
+    /// When the queue of a thread is full, [`ThreadPool::execute`] blocks until there is room,
+    /// [`ThreadPool::try_execute`] hands the job back and [`ThreadPool::execute_timeout`] waits
+    /// for a while. With [`work_stealing`](Builder::work_stealing) the shared queue of
+    /// `execute_rr` jobs holds up to `capacity * num_threads` jobs, see
+    /// [`ThreadPool::try_execute_rr`] and [`ThreadPool::execute_rr_timeout`].
+    ///
+    /// # Panics
+    ///
+    /// This method will panic if `capacity` is 0.
+    ///
+    /// # Examples
+    ///
+    /// ```rust, no_run
+    /// let pool = commlib::utils::ThreadPoolBuilder::new()
+    ///     .num_threads(4)
+    ///     .queue_capacity(1024)
+    ///     .build();
+    ///
+    /// if let Err(job) = pool.try_execute(0, || println!("save to db")) {
+    ///     // too busy, run it later or drop it
+    ///     drop(job);
+    /// }
+    /// ```
+    pub fn queue_capacity(mut self, capacity: usize) -> Builder {
+        assert!(capacity > 0);
+        self.queue_capacity = Some(capacity);
+        self
+    }
+
+    /// Set the handler called on the pool thread when a job panics, with the index of the
+    /// thread, the panic payload and the label given to
+    /// [`execute_with_label`](ThreadPool::execute_with_label). If not specified, the panic is
+    /// logged.
+    ///
+    /// The thread keeps running, the jobs queued behind the panicked one still run in order.
+    /// A panic in the handler itself is caught and logged, it is not counted again.
+    ///
+    /// # Examples
+    ///
+    /// ```rust, no_run
+    /// use commlib::utils::{panic_message, ThreadPoolBuilder};
+    ///
+    /// let pool = ThreadPoolBuilder::new()
+    ///     .num_threads(4)
+    ///     .panic_handler(|thread_index, payload, label| {
+    ///         eprintln!(
+    ///             "job {:?} panicked on thread {}: {:?}",
+    ///             label,
+    ///             thread_index,
+    ///             panic_message(payload)
+    ///         );
+    ///     })
+    ///     .build();
+    ///
+    /// pool.execute_with_label(0, "save player 42", || panic!("db is gone"));
+    /// ```
+    pub fn panic_handler<F>(mut self, handler: F) -> Builder
+    where
+        F: Fn(usize, &(dyn Any + Send), Option<&str>) + Send + Sync + 'static,
+    {
+        self.panic_handler = Some(Arc::new(handler));
+        self
+    }
+
     /// Finalize the [`Builder`] and build the [`ThreadPool`].
     ///
     /// [`Builder`]: struct.Builder.html
@@ -289,6 +537,33 @@ impl Builder {
     pub fn build(self) -> ThreadPool {
         let num_threads = self.num_threads.unwrap_or_else(num_cpus::get);
 
+        let mut locals: Vec<Option<deque::Worker<Thunk<'static>>>> = Vec::new();
+        let stealing = if self.work_stealing {
+            let workers: Vec<_> = (0..num_threads)
+                .map(|_| deque::Worker::new_fifo())
+                .collect();
+            let stealers = workers.iter().map(|w| w.stealer()).collect();
+            locals.extend(workers.into_iter().map(Some));
+
+            // a full wake channel means enough threads are already being woken up
+            let (wake_tx, wake_rx) = channel::bounded(num_threads);
+            Some(StealingQueue {
+                injector: deque::Injector::new(),
+                slot: SlotQueue::new(self.queue_capacity.map(|capacity| capacity * num_threads)),
+                stealers,
+                wake_tx,
+                wake_rx,
+            })
+        } else {
+            locals.resize_with(num_threads, || None);
+            None
+        };
+
+        let (jobs, receivers): (Vec<_>, Vec<_>) = (0..num_threads)
+            .map(|_| channel::unbounded::<Thunk<'static>>())
+            .unzip();
+        let (stop_tx, stop_rx) = channel::bounded(0);
+
         let shared_data = Arc::new(ThreadPoolSharedData {
             name: self.thread_name,
             empty_condvar: Condvar::new(),
@@ -300,16 +575,23 @@ impl Builder {
             max_thread_count: AtomicUsize::new(num_threads),
             panic_count: AtomicUsize::new(0),
             stack_size: self.thread_stack_size,
+            panic_handler: self.panic_handler,
 
             round_robin_id: AtomicUsize::new(0),
+            stealing,
+            slots: (0..num_threads)
+                .map(|_| SlotQueue::new(self.queue_capacity))
+                .collect(),
+            receivers,
+            shut_down: AtomicBool::new(false),
+            stop_tx: Mutex::new(Some(stop_tx)),
+            stop_rx,
         });
 
         // Threadpool threads
-        let mut jobs = Vec::new();
-        for i in 0..num_threads {
-            let (tx, rx) = channel::unbounded::<Thunk<'static>>();
-            spawn_in_pool(shared_data.clone(), rx, i + 1);
-            jobs.push(tx);
+        for (i, local) in locals.into_iter().enumerate() {
+            let rx = shared_data.receivers[i].clone();
+            spawn_in_pool(shared_data.clone(), rx, i + 1, local);
         }
 
         ThreadPool {
@@ -330,12 +612,253 @@ struct ThreadPoolSharedData {
     max_thread_count: AtomicUsize,
     panic_count: AtomicUsize,
     stack_size: Option<usize>,
+    panic_handler: Option<PanicHandler>,
 
     //
     round_robin_id: AtomicUsize,
+
+    // work-stealing scheduler for execute_rr, None if disabled
+    stealing: Option<StealingQueue>,
+
+    // queue length of each thread's channel
+    slots: Vec<SlotQueue>,
+
+    // the other ends of the job channels, used to drop the queued jobs on shutdown
+    receivers: Vec<channel::Receiver<Thunk<'static>>>,
+
+    // no more jobs are accepted
+    shut_down: AtomicBool,
+
+    // dropped to stop the threads
+    stop_tx: Mutex<Option<channel::Sender<()>>>,
+    stop_rx: channel::Receiver<()>,
+}
+
+struct SlotQueue {
+    // jobs sent or about to be sent, and not yet received
+    queued: AtomicUsize,
+    capacity: Option<usize>,
+
+    // signaled when a job leaves a bounded queue
+    space_trigger: Mutex<()>,
+    space_condvar: Condvar,
+}
+
+impl SlotQueue {
+    fn new(capacity: Option<usize>) -> Self {
+        Self {
+            queued: AtomicUsize::new(0),
+            capacity,
+            space_trigger: Mutex::new(()),
+            space_condvar: Condvar::new(),
+        }
+    }
+
+    /// Take a place in the queue if it isn't full.
+    fn try_reserve(&self) -> bool {
+        let capacity = match self.capacity {
+            Some(capacity) => capacity,
+            None => {
+                self.queued.fetch_add(1, Ordering::SeqCst);
+                return true;
+            }
+        };
+
+        let mut queued = self.queued.load(Ordering::SeqCst);
+        loop {
+            if queued >= capacity {
+                return false;
+            }
+            match self.queued.compare_exchange_weak(
+                queued,
+                queued + 1,
+                Ordering::SeqCst,
+                Ordering::SeqCst,
+            ) {
+                Ok(_) => return true,
+                Err(actual) => queued = actual,
+            }
+        }
+    }
+
+    /// Take a place in the queue, waiting until `deadline` (forever if `None`) for one.
+    fn reserve(&self, deadline: Option<Instant>) -> bool {
+        if self.try_reserve() {
+            return true;
+        }
+
+        let mut lock = self.space_trigger.lock();
+        loop {
+            // check again with the lock held, release() signals under the same lock
+            if self.try_reserve() {
+                return true;
+            }
+            match deadline {
+                Some(deadline) => {
+                    if self
+                        .space_condvar
+                        .wait_until(&mut lock, deadline)
+                        .timed_out()
+                    {
+                        return self.try_reserve();
+                    }
+                }
+                None => self.space_condvar.wait(&mut lock),
+            }
+        }
+    }
+
+    fn release(&self) {
+        self.queued.fetch_sub(1, Ordering::SeqCst);
+        if self.capacity.is_some() {
+            let _lock = self.space_trigger.lock();
+            self.space_condvar.notify_one();
+        }
+    }
+}
+
+struct StealingQueue {
+    injector: deque::Injector<Thunk<'static>>,
+    // jobs in the injector and the per-thread deques
+    slot: SlotQueue,
+    stealers: Vec<deque::Stealer<Thunk<'static>>>,
+
+    // wakes up idle threads blocked on their own channel
+    wake_tx: channel::Sender<()>,
+    wake_rx: channel::Receiver<()>,
+}
+
+impl StealingQueue {
+    fn push(&self, job: Thunk<'static>) {
+        self.injector.push(job);
+        self.wake_one();
+    }
+
+    #[inline(always)]
+    fn wake_one(&self) {
+        let _ = self.wake_tx.try_send(());
+    }
+
+    /// Steal a job from the injector or from any thread
+    fn steal_any(&self) -> Option<Thunk<'static>> {
+        loop {
+            let mut retry = false;
+            for steal in std::iter::once(self.injector.steal())
+                .chain(self.stealers.iter().map(|stealer| stealer.steal()))
+            {
+                match steal {
+                    deque::Steal::Success(job) => return Some(job),
+                    deque::Steal::Retry => retry = true,
+                    deque::Steal::Empty => {}
+                }
+            }
+            if !retry {
+                return None;
+            }
+        }
+    }
+
+    /// Pop from the local deque, then take a batch from the injector, then steal from the
+    /// other threads
+    fn find_job(
+        &self,
+        local: &deque::Worker<Thunk<'static>>,
+        index: usize,
+    ) -> Option<Thunk<'static>> {
+        if let Some(job) = local.pop() {
+            return Some(job);
+        }
+
+        loop {
+            let mut retry = false;
+
+            match self.injector.steal_batch_and_pop(local) {
+                deque::Steal::Success(job) => {
+                    if !local.is_empty() {
+                        // let the idle threads steal the rest of the batch
+                        self.wake_one();
+                    }
+                    return Some(job);
+                }
+                deque::Steal::Retry => retry = true,
+                deque::Steal::Empty => {}
+            }
+
+            let n = self.stealers.len();
+            for i in 1..n {
+                match self.stealers[(index + i) % n].steal() {
+                    deque::Steal::Success(job) => return Some(job),
+                    deque::Steal::Retry => retry = true,
+                    deque::Steal::Empty => {}
+                }
+            }
+
+            if !retry {
+                return None;
+            }
+        }
+    }
 }
 
 impl ThreadPoolSharedData {
+    /// Count a panicked job and hand it over to the panic handler, on the pool thread.
+    fn report_panic(&self, payload: &(dyn Any + Send), label: Option<&str>) {
+        self.panic_count.fetch_add(1, Ordering::SeqCst);
+
+        let thread_index = THREAD_INDEX.with(|index| index.get());
+        match &self.panic_handler {
+            Some(handler) => {
+                // a panicking handler would be counted again by the worker or the Sentinel
+                let result =
+                    panic::catch_unwind(AssertUnwindSafe(|| handler(thread_index, payload, label)));
+                if result.is_err() {
+                    log::error!(
+                        "ThreadPool({:?}) thread {} panic handler panicked",
+                        self.name,
+                        thread_index
+                    );
+                }
+            }
+            None => log::error!(
+                "ThreadPool({:?}) thread {} job {:?} panicked: {}",
+                self.name,
+                thread_index,
+                label,
+                panic_message(payload).unwrap_or("Box<dyn Any>")
+            ),
+        }
+    }
+
+    #[inline(always)]
+    fn is_shut_down(&self) -> bool {
+        self.shut_down.load(Ordering::SeqCst)
+    }
+
+    /// Drop every job which hasn't started yet, returns the number of dropped jobs.
+    fn discard_queued(&self) -> usize {
+        let mut discarded = 0;
+        for (rx, slot) in self.receivers.iter().zip(&self.slots) {
+            while let Ok(job) = rx.try_recv() {
+                slot.release();
+                self.queued_count.fetch_sub(1, Ordering::SeqCst);
+                drop(job);
+                discarded += 1;
+            }
+        }
+
+        if let Some(stealing) = &self.stealing {
+            while let Some(job) = stealing.steal_any() {
+                stealing.slot.release();
+                self.queued_count.fetch_sub(1, Ordering::SeqCst);
+                drop(job);
+                discarded += 1;
+            }
+        }
+
+        self.no_work_notify_all();
+        discarded
+    }
+
     #[inline(always)]
     fn has_work(&self) -> bool {
         self.queued_count.load(Ordering::SeqCst) > 0 || self.active_count.load(Ordering::SeqCst) > 0
@@ -434,30 +957,350 @@ impl ThreadPool {
     where
         F: FnOnce() + Send + 'static,
     {
+        self.execute_thunk(pos, Box::new(job));
+    }
+
+    /// Executes the function `job` on the thread of `pos` like [`execute`](ThreadPool::execute),
+    /// with a `label` telling where the job came from if it panics, see
+    /// [`Builder::panic_handler`].
+    ///
+    /// # Examples
+    ///
+    /// ```rust, no_run
+    /// use commlib::utils::ThreadPool;
+    ///
+    /// let pool = ThreadPool::new(2);
+    /// let player_id = 42;
+    /// pool.execute_with_label(player_id, format!("save player {}", player_id), || {
+    ///     println!("saving");
+    /// });
+    /// pool.execute_with_label(player_id, "notify friends", || println!("notifying"));
+    /// pool.join();
+    /// ```
+    pub fn execute_with_label<L, F>(&self, pos: usize, label: L, job: F)
+    where
+        L: Into<Cow<'static, str>>,
+        F: FnOnce() + Send + 'static,
+    {
+        let label = label.into();
+        let shared_data = self.shared_data.clone();
+        self.execute(pos, move || {
+            if let Err(payload) = panic::catch_unwind(AssertUnwindSafe(job)) {
+                shared_data.report_panic(&*payload, Some(&label));
+            }
+        });
+    }
+
+    #[inline(always)]
+    fn execute_thunk(&self, pos: usize, job: Thunk<'static>) {
+        if self.reject_after_shutdown() {
+            return;
+        }
+        let pos = self.slot_of(pos);
+        self.shared_data.slots[pos].reserve(None);
+
+        // shut down while waiting for room
+        if !self.count_queued() {
+            self.shared_data.slots[pos].release();
+            self.log_rejected();
+            return;
+        }
+        self.send_reserved(pos, job);
+    }
+
+    /// Executes the function `job` on the thread of `pos` if its queue isn't full, otherwise
+    /// hands `job` back.
+    ///
+    /// Without a [`queue_capacity`](Builder::queue_capacity) this always succeeds, unless the
+    /// pool was [shut down](ThreadPool::shutdown).
+    ///
+    /// # Examples
+    ///
+    /// ```rust, no_run
+    /// use commlib::utils::ThreadPoolBuilder;
+    ///
+    /// let pool = ThreadPoolBuilder::new().num_threads(1).queue_capacity(1).build();
+    /// for i in 0..10 {
+    ///     if let Err(job) = pool.try_execute(0, move || println!("job {}", i)) {
+    ///         // run it here instead
+    ///         job();
+    ///     }
+    /// }
+    /// ```
+    pub fn try_execute<F>(&self, pos: usize, job: F) -> Result<(), F>
+    where
+        F: FnOnce() + Send + 'static,
+    {
+        let pos = self.slot_of(pos);
+        if self.shared_data.is_shut_down() || !self.shared_data.slots[pos].try_reserve() {
+            return Err(job);
+        }
+        if !self.count_queued() {
+            self.shared_data.slots[pos].release();
+            return Err(job);
+        }
+        self.send_reserved(pos, Box::new(job));
+        Ok(())
+    }
+
+    /// Executes the function `job` on the thread of `pos`, waiting up to `timeout` for room in
+    /// its queue, otherwise hands `job` back.
+    ///
+    /// Without a [`queue_capacity`](Builder::queue_capacity) this always succeeds right away,
+    /// unless the pool was [shut down](ThreadPool::shutdown).
+    pub fn execute_timeout<F>(&self, pos: usize, job: F, timeout: Duration) -> Result<(), F>
+    where
+        F: FnOnce() + Send + 'static,
+    {
+        let pos = self.slot_of(pos);
+        if self.shared_data.is_shut_down()
+            || !self.shared_data.slots[pos].reserve(Some(Instant::now() + timeout))
+        {
+            return Err(job);
+        }
+        if !self.count_queued() {
+            self.shared_data.slots[pos].release();
+            return Err(job);
+        }
+        self.send_reserved(pos, Box::new(job));
+        Ok(())
+    }
+
+    #[inline(always)]
+    fn slot_of(&self, pos: usize) -> usize {
         let num_job_tx = self.job_tx_vec.len();
         assert!(num_job_tx >= 1);
+        pos % num_job_tx
+    }
+
+    // Jobs executed after shutdown are dropped
+    #[inline(always)]
+    fn reject_after_shutdown(&self) -> bool {
+        if self.shared_data.is_shut_down() {
+            self.log_rejected();
+            true
+        } else {
+            false
+        }
+    }
+
+    fn log_rejected(&self) {
+        log::error!(
+            "ThreadPool({:?}) is shut down, job is dropped",
+            self.shared_data.name
+        );
+    }
 
-        self.shared_data.queued_count.fetch_add(1, Ordering::SeqCst);
-        self.shared_data.total_count.fetch_add(1, Ordering::Relaxed);
+    // Counts a job as queued before it is sent. The shut down flag is checked again afterwards,
+    // so either `shutdown` sees the job and waits for it, or the job is rejected.
+    #[inline(always)]
+    fn count_queued(&self) -> bool {
+        let shared_data = &self.shared_data;
+        shared_data.queued_count.fetch_add(1, Ordering::SeqCst);
+        if shared_data.is_shut_down() {
+            shared_data.queued_count.fetch_sub(1, Ordering::SeqCst);
+            shared_data.no_work_notify_all();
+            return false;
+        }
+        shared_data.total_count.fetch_add(1, Ordering::Relaxed);
+        true
+    }
 
-        let pos = pos % num_job_tx;
+    // The job is counted and its place in the slot queue is already taken, so sending never
+    // goes over the capacity
+    #[inline(always)]
+    fn send_reserved(&self, pos: usize, job: Thunk<'static>) {
         let job_tx = &self.job_tx_vec[pos];
         job_tx
-            .send(Box::new(job))
+            .send(job)
             .expect("ThreadPool::execute unable to send job into queue.");
     }
 
+    /// Executes the function `job` on the thread of `pos` like [`execute`](ThreadPool::execute),
+    /// returning a [`JobHandle`] for its result.
+    ///
+    /// A panic in `job` is caught and handed over to the [`JobHandle`], the thread keeps running
+    /// and the panic is still counted in [`panic_count`](ThreadPool::panic_count).
+    ///
+    /// # Examples
+    ///
+    /// ```rust, no_run
+    /// use commlib::utils::ThreadPool;
+    ///
+    /// let pool = ThreadPool::new(2);
+    /// let handle = pool.submit(0, || 1 + 1);
+    /// assert_eq!(2, handle.join().unwrap());
+    ///
+    /// let handle = pool.submit(1, || -> u32 { panic!("oops") });
+    /// assert!(handle.join().is_err());
+    /// ```
+    pub fn submit<F, T>(&self, pos: usize, job: F) -> JobHandle<T>
+    where
+        F: FnOnce() -> T + Send + 'static,
+        T: Send + 'static,
+    {
+        let state = Arc::new(JobState::new());
+        let completer = JobCompleter(Some(state.clone()));
+        let shared_data = self.shared_data.clone();
+        self.execute(pos, move || {
+            let result = panic::catch_unwind(AssertUnwindSafe(job));
+            if let Err(payload) = &result {
+                shared_data.report_panic(&**payload, None);
+            }
+            completer.complete(result);
+        });
+        JobHandle { state }
+    }
+
+    /// Creates a [`Scope`] for jobs which borrow data from the caller, and blocks until all of
+    /// them are done.
+    ///
+    /// If any of the scoped jobs panicked, the first panic is resumed here once all of the jobs
+    /// are done.
+    ///
+    /// Scoped jobs dropped without running, after a [`shutdown`](ThreadPool::shutdown), are
+    /// counted as done.
+    ///
+    /// Creating a scope from a thread within the pool and running scoped jobs on that same
+    /// thread will cause a deadlock, like [`join`](ThreadPool::join).
+    ///
+    /// # Examples
+    ///
+    /// ```rust, no_run
+    /// use commlib::utils::ThreadPool;
+    /// use std::sync::atomic::{AtomicUsize, Ordering};
+    ///
+    /// let pool = ThreadPool::new(4);
+    /// let words = vec!["hello", "thread", "pool"];
+    /// let total = AtomicUsize::new(0);
+    ///
+    /// pool.scope(|s| {
+    ///     for word in &words {
+    ///         let total = &total;
+    ///         s.execute_rr(move || {
+    ///             total.fetch_add(word.len(), Ordering::Relaxed);
+    ///         });
+    ///     }
+    /// });
+    /// assert_eq!(15, total.load(Ordering::Relaxed));
+    /// ```
+    pub fn scope<'pool, 'scope, F, R>(&'pool self, f: F) -> R
+    where
+        F: FnOnce(&Scope<'pool, 'scope>) -> R,
+    {
+        let scope = Scope {
+            pool: self,
+            state: Arc::new(ScopeState::default()),
+            _marker: PhantomData,
+        };
+
+        // wait for the jobs even if `f` panics, they may still borrow the caller's data
+        let result = panic::catch_unwind(AssertUnwindSafe(|| f(&scope)));
+        scope.state.wait();
+
+        if let Some(payload) = scope.state.panic.lock().take() {
+            panic::resume_unwind(payload);
+        }
+        match result {
+            Ok(r) => r,
+            Err(payload) => panic::resume_unwind(payload),
+        }
+    }
+
     /// Executes the function `job` on a thread in the pool with round-robin scheduling.
+    ///
+    /// With [`work_stealing`](Builder::work_stealing) enabled the job runs on whichever thread
+    /// gets to it first.
+    ///
+    /// With a [`queue_capacity`](Builder::queue_capacity) this blocks until there is room in the
+    /// queue.
     #[inline(always)]
     pub fn execute_rr<F>(&self, job: F)
     where
         F: FnOnce() + Send + 'static,
     {
-        let pos = self
-            .shared_data
+        self.execute_rr_thunk(Box::new(job));
+    }
+
+    #[inline(always)]
+    fn execute_rr_thunk(&self, job: Thunk<'static>) {
+        if self.reject_after_shutdown() {
+            return;
+        }
+        if let Some(stealing) = &self.shared_data.stealing {
+            stealing.slot.reserve(None);
+
+            // shut down while waiting for room
+            if !self.count_queued() {
+                stealing.slot.release();
+                self.log_rejected();
+                return;
+            }
+            stealing.push(job);
+            return;
+        }
+
+        self.execute_thunk(self.next_rr_pos(), job);
+    }
+
+    /// Executes the function `job` on a thread in the pool with round-robin scheduling, like
+    /// [`execute_rr`](ThreadPool::execute_rr), if there is room in the queue, otherwise hands
+    /// `job` back.
+    ///
+    /// With [`work_stealing`](Builder::work_stealing) the room is in the shared queue, otherwise
+    /// in the queue of the next thread.
+    pub fn try_execute_rr<F>(&self, job: F) -> Result<(), F>
+    where
+        F: FnOnce() + Send + 'static,
+    {
+        match &self.shared_data.stealing {
+            Some(stealing) => {
+                if self.shared_data.is_shut_down() || !stealing.slot.try_reserve() {
+                    return Err(job);
+                }
+                self.push_reserved(stealing, job)
+            }
+            None => self.try_execute(self.next_rr_pos(), job),
+        }
+    }
+
+    /// Executes the function `job` on a thread in the pool with round-robin scheduling, waiting
+    /// up to `timeout` for room in the queue, otherwise hands `job` back.
+    pub fn execute_rr_timeout<F>(&self, job: F, timeout: Duration) -> Result<(), F>
+    where
+        F: FnOnce() + Send + 'static,
+    {
+        match &self.shared_data.stealing {
+            Some(stealing) => {
+                if self.shared_data.is_shut_down()
+                    || !stealing.slot.reserve(Some(Instant::now() + timeout))
+                {
+                    return Err(job);
+                }
+                self.push_reserved(stealing, job)
+            }
+            None => self.execute_timeout(self.next_rr_pos(), job, timeout),
+        }
+    }
+
+    #[inline(always)]
+    fn next_rr_pos(&self) -> usize {
+        self.shared_data
             .round_robin_id
-            .fetch_add(1, Ordering::SeqCst);
-        self.execute(pos, job);
+            .fetch_add(1, Ordering::SeqCst)
+    }
+
+    // The place in the shared queue is already taken
+    fn push_reserved<F>(&self, stealing: &StealingQueue, job: F) -> Result<(), F>
+    where
+        F: FnOnce() + Send + 'static,
+    {
+        if !self.count_queued() {
+            stealing.slot.release();
+            return Err(job);
+        }
+        stealing.push(Box::new(job));
+        Ok(())
     }
 
     /// Returns the number of jobs waiting to executed in the pool.
@@ -484,6 +1327,41 @@ impl ThreadPool {
         self.shared_data.queued_count.load(Ordering::Relaxed)
     }
 
+    /// Returns the number of jobs waiting in the queue of the thread of `pos`.
+    ///
+    /// Jobs waiting in the work-stealing injector are not in any thread's queue.
+    ///
+    /// # Examples
+    ///
+    /// ```rust, no_run
+    /// use commlib::utils::ThreadPool;
+    /// use std::time::Duration;
+    /// use std::thread::sleep;
+    ///
+    /// let pool = ThreadPool::new(2);
+    /// for _ in 0..10 {
+    ///     pool.execute(1, || {
+    ///         sleep(Duration::from_secs(100));
+    ///     });
+    /// }
+    ///
+    /// sleep(Duration::from_secs(1)); // wait for threads to start
+    /// assert_eq!(0, pool.slot_queued_count(0));
+    /// assert_eq!(9, pool.slot_queued_count(1));
+    /// ```
+    #[inline(always)]
+    pub fn slot_queued_count(&self, pos: usize) -> usize {
+        let pos = self.slot_of(pos);
+        self.shared_data.slots[pos].queued.load(Ordering::Relaxed)
+    }
+
+    /// Returns the maximum number of jobs waiting in the queue of each thread, `None` if the
+    /// queues are unbounded.
+    #[inline(always)]
+    pub fn queue_capacity(&self) -> Option<usize> {
+        self.shared_data.slots[0].capacity
+    }
+
     /// Returns the number of total jobs already executed in the pool.
     #[inline(always)]
     pub fn total_count(&self) -> usize {
@@ -529,7 +1407,7 @@ impl ThreadPool {
         self.shared_data.max_thread_count.load(Ordering::Relaxed)
     }
 
-    /// Returns the number of panicked threads over the lifetime of the pool.
+    /// Returns the number of panicked jobs over the lifetime of the pool.
     ///
     /// # Examples
     ///
@@ -600,13 +1478,90 @@ impl ThreadPool {
             self.shared_data.empty_condvar.wait(&mut lock);
         }
 
-        // increase generation if we are the first thread to come out of the loop
-        let _ = self.shared_data.join_generation.compare_exchange(
-            generation,
-            generation.wrapping_add(1),
-            Ordering::SeqCst,
-            Ordering::SeqCst,
-        );
+        // increase generation if we are the first thread to come out of the loop
+        let _ = self.shared_data.join_generation.compare_exchange(
+            generation,
+            generation.wrapping_add(1),
+            Ordering::SeqCst,
+            Ordering::SeqCst,
+        );
+    }
+
+    /// Stop the pool: no more jobs are accepted, the queued jobs are finished or dropped
+    /// according to `policy`, and the threads exit once they are idle.
+    ///
+    /// Blocks for at most `timeout`. Jobs still queued at the deadline are dropped, jobs still
+    /// running keep running in the background; both are counted in the returned
+    /// [`ShutdownReport`]. Jobs executed after the shutdown are dropped, `try_execute` and
+    /// `execute_timeout` hand them back.
+    ///
+    /// Calling `shutdown` from a thread within the pool will wait for its own job until the
+    /// deadline.
+    ///
+    /// # Examples
+    ///
+    /// ```rust, no_run
+    /// use commlib::utils::{DrainPolicy, ThreadPool};
+    /// use std::thread::sleep;
+    /// use std::time::Duration;
+    ///
+    /// let pool = ThreadPool::new(1);
+    /// pool.execute(0, || sleep(Duration::from_secs(1)));
+    /// for _ in 0..10 {
+    ///     pool.execute(0, || println!("never runs"));
+    /// }
+    /// sleep(Duration::from_millis(100)); // wait for threads to start
+    ///
+    /// let report = pool.shutdown(DrainPolicy::Discard, Duration::from_secs(5));
+    /// assert_eq!(10, report.discarded);
+    /// assert_eq!(0, report.running);
+    /// ```
+    pub fn shutdown(&self, policy: DrainPolicy, timeout: Duration) -> ShutdownReport {
+        let deadline = Instant::now() + timeout;
+        let shared_data = &self.shared_data;
+        shared_data.shut_down.store(true, Ordering::SeqCst);
+
+        let mut discarded = match policy {
+            DrainPolicy::Finish => 0,
+            DrainPolicy::Discard => shared_data.discard_queued(),
+        };
+
+        {
+            let mut lock = shared_data.empty_trigger.lock();
+            while shared_data.has_work() {
+                if shared_data
+                    .empty_condvar
+                    .wait_until(&mut lock, deadline)
+                    .timed_out()
+                {
+                    break;
+                }
+            }
+        }
+
+        // out of time, drop what is left once the threads are told to stop
+        shared_data.stop_tx.lock().take();
+        discarded += shared_data.discard_queued();
+
+        let report = ShutdownReport {
+            discarded,
+            running: shared_data.active_count.load(Ordering::SeqCst),
+        };
+        if !report.is_clean() {
+            log::warn!(
+                "ThreadPool({:?}) shutdown: {} jobs discarded, {} jobs still running",
+                shared_data.name,
+                report.discarded,
+                report.running
+            );
+        }
+        report
+    }
+
+    /// Whether [`shutdown`](ThreadPool::shutdown) was called on the pool.
+    #[inline(always)]
+    pub fn is_shut_down(&self) -> bool {
+        self.shared_data.is_shut_down()
     }
 }
 
@@ -703,10 +1658,277 @@ impl PartialEq for ThreadPool {
 }
 impl Eq for ThreadPool {}
 
+/// What [`ThreadPool::shutdown`] does with the jobs which haven't started yet.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum DrainPolicy {
+    /// Run them before stopping
+    Finish,
+    /// Drop them, only the running jobs are waited for
+    Discard,
+}
+
+/// Jobs left unfinished by [`ThreadPool::shutdown`].
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct ShutdownReport {
+    /// Jobs dropped without running
+    pub discarded: usize,
+    /// Jobs still running at the deadline
+    pub running: usize,
+}
+
+impl ShutdownReport {
+    /// Whether every job was finished.
+    pub fn is_clean(&self) -> bool {
+        self.discarded == 0 && self.running == 0
+    }
+}
+
+type JobResult<T> = thread::Result<T>;
+
+struct JobState<T> {
+    slot: Mutex<JobSlot<T>>,
+    done: Condvar,
+}
+
+struct JobSlot<T> {
+    result: Option<JobResult<T>>,
+    finished: bool,
+    waker: Option<Waker>,
+}
+
+impl<T> JobState<T> {
+    fn new() -> Self {
+        Self {
+            slot: Mutex::new(JobSlot {
+                result: None,
+                finished: false,
+                waker: None,
+            }),
+            done: Condvar::new(),
+        }
+    }
+
+    fn finish(&self, result: JobResult<T>) {
+        let waker = {
+            let mut slot = self.slot.lock();
+            slot.result = Some(result);
+            slot.finished = true;
+            slot.waker.take()
+        };
+        self.done.notify_all();
+        if let Some(waker) = waker {
+            waker.wake();
+        }
+    }
+}
+
+// Hands the result over to the JobHandle, a job dropped without running reports an error
+struct JobCompleter<T>(Option<Arc<JobState<T>>>);
+
+impl<T> JobCompleter<T> {
+    fn complete(mut self, result: JobResult<T>) {
+        if let Some(state) = self.0.take() {
+            state.finish(result);
+        }
+    }
+}
+
+impl<T> Drop for JobCompleter<T> {
+    fn drop(&mut self) {
+        if let Some(state) = self.0.take() {
+            state.finish(Err(Box::new("job dropped before running")));
+        }
+    }
+}
+
+/// Handle to the result of a job created by [`ThreadPool::submit`].
+///
+/// The result is `Err` with the panic payload if the job panicked, or if it was dropped without
+/// running. Dropping the handle doesn't cancel the job.
+///
+/// `JobHandle` is also a [`Future`], so it can be `.await`ed from async code.
+pub struct JobHandle<T> {
+    state: Arc<JobState<T>>,
+}
+
+impl<T> JobHandle<T> {
+    /// Block the current thread until the job is done, and return its result.
+    ///
+    /// Calling `join` from the thread the job was submitted to will cause a deadlock.
+    pub fn join(self) -> JobResult<T> {
+        let mut slot = self.state.slot.lock();
+        while !slot.finished {
+            self.state.done.wait(&mut slot);
+        }
+        slot.result
+            .take()
+            .expect("JobHandle result was already taken by try_get")
+    }
+
+    /// Take the result if the job is done, without blocking.
+    ///
+    /// Returns `None` if the job is still queued or running, or if the result was already taken.
+    pub fn try_get(&mut self) -> Option<JobResult<T>> {
+        self.state.slot.lock().result.take()
+    }
+
+    /// Whether the job is done.
+    pub fn is_finished(&self) -> bool {
+        self.state.slot.lock().finished
+    }
+}
+
+impl<T> Future for JobHandle<T> {
+    type Output = JobResult<T>;
+
+    fn poll(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Self::Output> {
+        let mut slot = self.state.slot.lock();
+        if slot.finished {
+            let result = slot
+                .result
+                .take()
+                .expect("JobHandle polled after the result was taken");
+            Poll::Ready(result)
+        } else {
+            slot.waker = Some(cx.waker().clone());
+            Poll::Pending
+        }
+    }
+}
+
+impl<T> fmt::Debug for JobHandle<T> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.debug_struct("JobHandle")
+            .field("finished", &self.is_finished())
+            .finish()
+    }
+}
+
+#[derive(Default)]
+struct ScopeState {
+    pending: Mutex<usize>,
+    done: Condvar,
+    panic: Mutex<Option<Box<dyn Any + Send>>>,
+}
+
+impl ScopeState {
+    fn start(&self) {
+        *self.pending.lock() += 1;
+    }
+
+    fn complete(&self, result: JobResult<()>) {
+        if let Err(payload) = result {
+            // keep the first one
+            let mut panic = self.panic.lock();
+            if panic.is_none() {
+                *panic = Some(payload);
+            }
+        }
+
+        let mut pending = self.pending.lock();
+        *pending -= 1;
+        if *pending == 0 {
+            self.done.notify_all();
+        }
+    }
+
+    fn wait(&self) {
+        let mut pending = self.pending.lock();
+        while *pending > 0 {
+            self.done.wait(&mut pending);
+        }
+    }
+}
+
+// Completes a scoped job, a job dropped without running (rejected after shutdown, or
+// discarded) is completed on drop so that ThreadPool::scope doesn't wait for it forever
+struct ScopeJob<F> {
+    job: Option<F>,
+    state: Option<Arc<ScopeState>>,
+}
+
+impl<F> ScopeJob<F> {
+    fn complete(&mut self, result: JobResult<()>) {
+        if let Some(state) = self.state.take() {
+            state.complete(result);
+        }
+    }
+}
+
+impl<F> Drop for ScopeJob<F> {
+    fn drop(&mut self) {
+        // the job may borrow for 'scope, drop it before the scope can end
+        drop(self.job.take());
+        self.complete(Ok(()));
+    }
+}
+
+/// Jobs created through a `Scope` can borrow data living longer than the scope, see
+/// [`ThreadPool::scope`].
+pub struct Scope<'pool, 'scope> {
+    pool: &'pool ThreadPool,
+    state: Arc<ScopeState>,
+    // invariant over 'scope
+    _marker: PhantomData<std::cell::Cell<&'scope mut ()>>,
+}
+
+impl<'pool, 'scope> Scope<'pool, 'scope> {
+    /// Executes the function `job` on the thread of `pos`, like [`ThreadPool::execute`].
+    pub fn execute<F>(&self, pos: usize, job: F)
+    where
+        F: FnOnce() + Send + 'scope,
+    {
+        let job = self.wrap(job);
+        self.pool.execute_thunk(pos, job);
+    }
+
+    /// Executes the function `job` on any thread, like [`ThreadPool::execute_rr`].
+    pub fn execute_rr<F>(&self, job: F)
+    where
+        F: FnOnce() + Send + 'scope,
+    {
+        let job = self.wrap(job);
+        self.pool.execute_rr_thunk(job);
+    }
+
+    fn wrap<F>(&self, job: F) -> Thunk<'static>
+    where
+        F: FnOnce() + Send + 'scope,
+    {
+        self.state.start();
+        let mut scope_job = ScopeJob {
+            job: Some(job),
+            state: Some(self.state.clone()),
+        };
+        let shared_data = self.pool.shared_data.clone();
+        let job: Thunk<'scope> = Box::new(move || {
+            let job = scope_job.job.take().unwrap();
+            let result = panic::catch_unwind(AssertUnwindSafe(job));
+            if let Err(payload) = &result {
+                shared_data.report_panic(&**payload, None);
+            }
+            scope_job.complete(result);
+        });
+
+        // SAFETY: ThreadPool::scope doesn't return before every job has completed, so nothing
+        // borrowed for 'scope is used after it ends.
+        unsafe { std::mem::transmute::<Thunk<'scope>, Thunk<'static>>(job) }
+    }
+}
+
+impl fmt::Debug for Scope<'_, '_> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.debug_struct("Scope")
+            .field("pending", &*self.state.pending.lock())
+            .finish()
+    }
+}
+
 fn spawn_in_pool(
     shared_data: Arc<ThreadPoolSharedData>,
     rx: channel::Receiver<Thunk<'static>>,
     rx_index: usize,
+    local: Option<deque::Worker<Thunk<'static>>>,
 ) {
     let mut builder = thread::Builder::new();
     if let Some(ref name) = shared_data.name {
@@ -723,7 +1945,8 @@ fn spawn_in_pool(
     builder
         .spawn(move || {
             // Will spawn a new thread on panic unless it is cancelled.
-            let sentinel = Sentinel::new(&shared_data, rx.clone(), rx_index);
+            let sentinel = Sentinel::new(&shared_data, rx, rx_index, local);
+            THREAD_INDEX.with(|index| index.set(rx_index - 1));
 
             loop {
                 // Shutdown this thread if the pool has become smaller
@@ -734,18 +1957,20 @@ fn spawn_in_pool(
                 }
 
                 //
-                let message = rx.recv();
-                let job = match message {
-                    Ok(job) => job,
+                let job = match sentinel.next_job() {
+                    Some(job) => job,
                     // The ThreadPool was dropped.
-                    Err(..) => break,
+                    None => break,
                 };
 
                 // Do not allow IR around the job execution
                 shared_data.active_count.fetch_add(1, Ordering::SeqCst);
                 shared_data.queued_count.fetch_sub(1, Ordering::SeqCst);
 
-                job.call_box();
+                // the thread goes on with the jobs queued behind a panicked one, in order
+                if let Err(payload) = panic::catch_unwind(AssertUnwindSafe(|| job.call_box())) {
+                    shared_data.report_panic(&*payload, None);
+                }
 
                 shared_data.active_count.fetch_sub(1, Ordering::SeqCst);
                 shared_data.no_work_notify_all();
@@ -763,7 +1988,7 @@ mod test {
     use std::thread::{self, sleep};
     use std::time::Duration;
 
-    use super::ThreadPool;
+    use super::{Builder as ThreadPoolBuilder, DrainPolicy, ShutdownReport, ThreadPool};
 
     const TEST_TASKS: usize = 4;
 
@@ -1107,6 +2332,516 @@ mod test {
         );
     }
 
+    #[test]
+    fn test_work_stealing() {
+        let pool = ThreadPoolBuilder::new()
+            .num_threads(TEST_TASKS)
+            .work_stealing(true)
+            .build();
+
+        // one job blocks its thread until all the others are done
+        let (block_tx, block_rx) = std::sync::mpsc::channel::<()>();
+        pool.execute_rr(move || {
+            let _ = block_rx.recv_timeout(Duration::from_secs(10));
+        });
+
+        let (tx, rx) = std::sync::mpsc::channel();
+        for i in 0..8 * TEST_TASKS {
+            let tx = tx.clone();
+            pool.execute_rr(move || {
+                sleep(Duration::from_millis(1));
+                tx.send(i).unwrap();
+            });
+        }
+        let mut done: Vec<usize> = (0..8 * TEST_TASKS)
+            .map(|_| rx.recv_timeout(Duration::from_secs(5)).unwrap())
+            .collect();
+        done.sort_unstable();
+        assert_eq!(done, (0..8 * TEST_TASKS).collect::<Vec<_>>());
+        block_tx.send(()).unwrap();
+
+        // execute(pos, ..) keeps the order and the thread of each slot
+        let (tx, rx) = std::sync::mpsc::channel();
+        for i in 0..4 * TEST_TASKS {
+            let tx = tx.clone();
+            pool.execute(i % 2, move || {
+                tx.send((i % 2, i, thread::current().id())).unwrap();
+            });
+        }
+        pool.join();
+        let mut slots: Vec<Vec<(usize, thread::ThreadId)>> = vec![vec![], vec![]];
+        for (pos, i, id) in rx.try_iter() {
+            slots[pos].push((i, id));
+        }
+        for slot in slots {
+            assert_eq!(slot.len(), 2 * TEST_TASKS);
+            assert!(slot.windows(2).all(|w| w[0].0 < w[1].0 && w[0].1 == w[1].1));
+        }
+        assert_eq!(pool.queued_count(), 0);
+        assert_eq!(pool.total_count(), 1 + 12 * TEST_TASKS);
+    }
+
+    #[test]
+    fn test_work_stealing_recovery_from_panic() {
+        let pool = ThreadPoolBuilder::new()
+            .num_threads(2)
+            .work_stealing(true)
+            .build();
+        for _ in 0..4 {
+            pool.execute_rr(move || panic!("Ignore this panic, it must!"));
+        }
+        pool.join();
+        assert_eq!(pool.panic_count(), 4);
+
+        let (tx, rx) = std::sync::mpsc::channel();
+        for _ in 0..TEST_TASKS {
+            let tx = tx.clone();
+            pool.execute_rr(move || {
+                tx.send(1).unwrap();
+            });
+        }
+        assert_eq!(rx.iter().take(TEST_TASKS).sum::<usize>(), TEST_TASKS);
+    }
+
+    #[test]
+    fn test_submit() {
+        let pool = ThreadPool::new(TEST_TASKS);
+
+        let handles: Vec<_> = (0..TEST_TASKS)
+            .map(|i| pool.submit(i, move || i * 10))
+            .collect();
+        let results: Vec<usize> = handles.into_iter().map(|h| h.join().unwrap()).collect();
+        assert_eq!(results, vec![0, 10, 20, 30]);
+
+        // try_get doesn't block
+        let (tx, rx) = std::sync::mpsc::channel::<()>();
+        let mut handle = pool.submit(0, move || {
+            rx.recv().unwrap();
+            "done"
+        });
+        assert!(handle.try_get().is_none());
+        assert!(!handle.is_finished());
+        tx.send(()).unwrap();
+        while !handle.is_finished() {
+            sleep(Duration::from_millis(1));
+        }
+        assert_eq!(handle.try_get().unwrap().unwrap(), "done");
+        assert!(handle.try_get().is_none());
+
+        // a panic goes to the handle, the thread survives
+        let handle = pool.submit(1, || -> usize { panic!("Ignore this panic, it must!") });
+        assert!(handle.join().is_err());
+        assert_eq!(pool.panic_count(), 1);
+        assert_eq!(pool.submit(1, || 1).join().unwrap(), 1);
+    }
+
+    #[test]
+    fn test_submit_future() {
+        use std::future::Future;
+        use std::pin::pin;
+        use std::task::{Context, Poll, Wake, Waker};
+
+        struct ThreadWaker(thread::Thread);
+
+        impl Wake for ThreadWaker {
+            fn wake(self: Arc<Self>) {
+                self.0.unpark();
+            }
+        }
+
+        let pool = ThreadPool::new(2);
+        let waker = Waker::from(Arc::new(ThreadWaker(thread::current())));
+        let mut cx = Context::from_waker(&waker);
+
+        let mut handle = pin!(pool.submit(1, || {
+            sleep(Duration::from_millis(50));
+            "async"
+        }));
+        let result = loop {
+            match handle.as_mut().poll(&mut cx) {
+                Poll::Ready(result) => break result,
+                Poll::Pending => thread::park(),
+            }
+        };
+        assert_eq!(result.unwrap(), "async");
+    }
+
+    #[test]
+    fn test_scope() {
+        let pool = ThreadPool::new(TEST_TASKS);
+
+        let mut numbers: Vec<usize> = (0..100).collect();
+        let offset = 1000;
+        pool.scope(|s| {
+            for (pos, chunk) in numbers.chunks_mut(10).enumerate() {
+                let offset = &offset;
+                s.execute(pos, move || {
+                    sleep(Duration::from_millis(5));
+                    chunk.iter_mut().for_each(|n| *n += *offset);
+                });
+            }
+        });
+        assert_eq!(numbers, (1000..1100).collect::<Vec<_>>());
+
+        // the scope returns a value, and jobs can be nested in other scopes
+        let sum = pool.scope(|s| {
+            let total = AtomicUsize::new(0);
+            pool.scope(|inner| {
+                for n in &numbers {
+                    let total = &total;
+                    inner.execute_rr(move || {
+                        total.fetch_add(*n, Ordering::Relaxed);
+                    });
+                }
+            });
+            s.execute(0, || {});
+            total.load(Ordering::Relaxed)
+        });
+        assert_eq!(sum, (1000..1100).sum());
+
+        // a panic in a job is resumed once the others are done
+        let finished = AtomicUsize::new(0);
+        let result = std::panic::catch_unwind(std::panic::AssertUnwindSafe(|| {
+            pool.scope(|s| {
+                s.execute(0, || panic!("Ignore this panic, it must!"));
+                for i in 1..TEST_TASKS {
+                    let finished = &finished;
+                    s.execute(i, move || {
+                        sleep(Duration::from_millis(20));
+                        finished.fetch_add(1, Ordering::SeqCst);
+                    });
+                }
+            });
+        }));
+        assert!(result.is_err());
+        assert_eq!(finished.load(Ordering::SeqCst), TEST_TASKS - 1);
+    }
+
+    #[test]
+    fn test_queue_capacity() {
+        let pool = ThreadPoolBuilder::new()
+            .num_threads(2)
+            .queue_capacity(2)
+            .build();
+        assert_eq!(pool.queue_capacity(), Some(2));
+
+        // keep thread 0 busy
+        let (block_tx, block_rx) = std::sync::mpsc::channel::<()>();
+        let (started_tx, started_rx) = std::sync::mpsc::channel::<()>();
+        pool.execute(0, move || {
+            started_tx.send(()).unwrap();
+            block_rx.recv().unwrap();
+        });
+        started_rx.recv().unwrap();
+        assert_eq!(pool.slot_queued_count(0), 0);
+
+        let counter = Arc::new(AtomicUsize::new(0));
+        let job = |counter: &Arc<AtomicUsize>| {
+            let counter = counter.clone();
+            move || {
+                counter.fetch_add(1, Ordering::SeqCst);
+            }
+        };
+        assert!(pool.try_execute(0, job(&counter)).is_ok());
+        assert!(pool.try_execute(2, job(&counter)).is_ok());
+        assert_eq!(pool.slot_queued_count(0), 2);
+
+        // full: the job comes back
+        let rejected = pool.try_execute(0, job(&counter)).unwrap_err();
+        rejected();
+        assert_eq!(counter.load(Ordering::SeqCst), 1);
+
+        let start = std::time::Instant::now();
+        assert!(pool
+            .execute_timeout(0, job(&counter), Duration::from_millis(50))
+            .is_err());
+        assert!(start.elapsed() >= Duration::from_millis(50));
+
+        // other threads are not affected
+        assert!(pool.try_execute(1, job(&counter)).is_ok());
+
+        // room is made while waiting
+        let unblock = thread::spawn(move || {
+            sleep(Duration::from_millis(50));
+            block_tx.send(()).unwrap();
+        });
+        assert!(pool
+            .execute_timeout(0, job(&counter), Duration::from_secs(5))
+            .is_ok());
+        pool.execute(0, job(&counter));
+        unblock.join().unwrap();
+        pool.join();
+
+        assert_eq!(counter.load(Ordering::SeqCst), 6);
+        assert_eq!(pool.slot_queued_count(0), 0);
+        assert_eq!(pool.queued_count(), 0);
+    }
+
+    #[test]
+    fn test_queue_capacity_work_stealing() {
+        let pool = ThreadPoolBuilder::new()
+            .num_threads(2)
+            .work_stealing(true)
+            .queue_capacity(1)
+            .build();
+        let counter = Arc::new(AtomicUsize::new(0));
+        let job = |counter: &Arc<AtomicUsize>| {
+            let counter = counter.clone();
+            move || {
+                counter.fetch_add(1, Ordering::SeqCst);
+            }
+        };
+
+        // keep both threads busy
+        let barrier = Arc::new(Barrier::new(3));
+        let (block_tx, block_rx) = crossbeam_channel::unbounded::<()>();
+        for i in 0..2 {
+            let barrier = barrier.clone();
+            let block_rx = block_rx.clone();
+            pool.execute(i, move || {
+                barrier.wait();
+                block_rx.recv().unwrap();
+            });
+        }
+        barrier.wait();
+
+        // the shared queue holds capacity * num_threads jobs
+        assert!(pool.try_execute_rr(job(&counter)).is_ok());
+        assert!(pool.try_execute_rr(job(&counter)).is_ok());
+        assert!(pool.try_execute_rr(job(&counter)).is_err());
+        assert!(pool
+            .execute_rr_timeout(job(&counter), Duration::from_millis(50))
+            .is_err());
+
+        // room is made while waiting
+        let unblock = thread::spawn(move || {
+            sleep(Duration::from_millis(50));
+            block_tx.send(()).unwrap();
+            block_tx.send(()).unwrap();
+        });
+        assert!(pool
+            .execute_rr_timeout(job(&counter), Duration::from_secs(5))
+            .is_ok());
+        pool.execute_rr(job(&counter));
+        unblock.join().unwrap();
+        pool.join();
+
+        assert_eq!(counter.load(Ordering::SeqCst), 4);
+        assert_eq!(pool.queued_count(), 0);
+    }
+
+    #[test]
+    fn test_shutdown_finish() {
+        let pool = ThreadPool::new(2);
+        let counter = Arc::new(AtomicUsize::new(0));
+        for i in 0..20 {
+            let counter = counter.clone();
+            pool.execute(i, move || {
+                sleep(Duration::from_millis(5));
+                counter.fetch_add(1, Ordering::SeqCst);
+            });
+        }
+
+        let report = pool.shutdown(DrainPolicy::Finish, Duration::from_secs(5));
+        assert!(report.is_clean());
+        assert_eq!(counter.load(Ordering::SeqCst), 20);
+        assert!(pool.is_shut_down());
+
+        // rejected afterwards
+        let clone = pool.clone();
+        clone.execute(0, || panic!("never runs"));
+        assert!(clone.try_execute(0, || ()).is_err());
+        assert!(pool.submit(1, || 1).join().is_err());
+        assert_eq!(pool.queued_count(), 0);
+
+        // the threads exit
+        sleep(Duration::from_millis(100));
+        assert_eq!(Arc::strong_count(&pool.shared_data), 2);
+    }
+
+    #[test]
+    fn test_shutdown_discard_and_deadline() {
+        for work_stealing in [false, true] {
+            let pool = ThreadPoolBuilder::new()
+                .num_threads(2)
+                .work_stealing(work_stealing)
+                .build();
+            let (started_tx, started_rx) = std::sync::mpsc::channel::<()>();
+            for i in 0..2 {
+                let started_tx = started_tx.clone();
+                pool.execute(i, move || {
+                    started_tx.send(()).unwrap();
+                    sleep(Duration::from_millis(300));
+                });
+            }
+            started_rx.recv().unwrap();
+            started_rx.recv().unwrap();
+
+            let handle = pool.submit(0, || 1);
+            for i in 0..10 {
+                pool.execute(i, || panic!("never runs"));
+                pool.execute_rr(|| panic!("never runs"));
+            }
+
+            // the running jobs outlast the deadline
+            let report = pool.shutdown(DrainPolicy::Discard, Duration::from_millis(50));
+            assert_eq!(
+                report,
+                ShutdownReport {
+                    discarded: 21,
+                    running: 2
+                }
+            );
+            assert!(handle.join().is_err());
+            assert_eq!(pool.queued_count(), 0);
+            assert_eq!(pool.slot_queued_count(0), 0);
+
+            pool.join();
+            assert_eq!(pool.panic_count(), 0);
+        }
+
+        // Finish runs out of time: the rest is dropped
+        let pool = ThreadPool::new(1);
+        for _ in 0..10 {
+            pool.execute(0, || sleep(Duration::from_millis(40)));
+        }
+        let report = pool.shutdown(DrainPolicy::Finish, Duration::from_millis(100));
+        assert_eq!(report.running, 1);
+        assert!(report.discarded >= 6 && report.discarded <= 8);
+    }
+
+    #[test]
+    fn test_scope_after_shutdown() {
+        for work_stealing in [false, true] {
+            let pool = ThreadPoolBuilder::new()
+                .num_threads(2)
+                .work_stealing(work_stealing)
+                .build();
+            assert!(pool
+                .shutdown(DrainPolicy::Finish, Duration::from_secs(5))
+                .is_clean());
+
+            // rejected scoped jobs don't block the scope
+            let counter = AtomicUsize::new(0);
+            pool.scope(|s| {
+                s.execute(0, || {
+                    counter.fetch_add(1, Ordering::SeqCst);
+                });
+                s.execute_rr(|| {
+                    counter.fetch_add(1, Ordering::SeqCst);
+                });
+            });
+            assert_eq!(counter.load(Ordering::SeqCst), 0);
+        }
+    }
+
+    #[test]
+    fn test_scope_shutdown_discard() {
+        for work_stealing in [false, true] {
+            let pool = ThreadPoolBuilder::new()
+                .num_threads(1)
+                .work_stealing(work_stealing)
+                .build();
+            let counter = AtomicUsize::new(0);
+            let (started_tx, started_rx) = std::sync::mpsc::channel::<()>();
+            pool.scope(|s| {
+                s.execute(0, move || {
+                    started_tx.send(()).unwrap();
+                    sleep(Duration::from_millis(100));
+                });
+                for i in 0..10 {
+                    s.execute(i, || {
+                        counter.fetch_add(1, Ordering::SeqCst);
+                    });
+                    s.execute_rr(|| {
+                        counter.fetch_add(1, Ordering::SeqCst);
+                    });
+                }
+                started_rx.recv().unwrap();
+
+                let report = pool.shutdown(DrainPolicy::Discard, Duration::from_secs(5));
+                assert_eq!(report.discarded, 20);
+            });
+            assert_eq!(counter.load(Ordering::SeqCst), 0);
+            assert_eq!(pool.queued_count(), 0);
+        }
+    }
+
+    #[test]
+    fn test_panic_handler() {
+        let reports = Arc::new(parking_lot::Mutex::new(Vec::new()));
+        let r = reports.clone();
+        let pool = ThreadPoolBuilder::new()
+            .num_threads(2)
+            .panic_handler(move |thread_index, payload, label| {
+                r.lock().push((
+                    thread_index,
+                    super::panic_message(payload).map(str::to_owned),
+                    label.map(str::to_owned),
+                ));
+            })
+            .build();
+
+        let (tx, rx) = std::sync::mpsc::channel();
+        for i in 0..6 {
+            let tx = tx.clone();
+            let job = move || {
+                if i == 2 {
+                    panic!("Ignore this panic, it must!");
+                }
+                tx.send((i, thread::current().id())).unwrap();
+            };
+            if i % 2 == 0 {
+                pool.execute_with_label(1, format!("job {}", i), job);
+            } else {
+                pool.execute(1, job);
+            }
+        }
+        pool.execute(0, || panic!("{}", 42));
+        pool.submit(3, || panic!("Ignore this panic, it must!"))
+            .join()
+            .unwrap_err();
+        pool.join();
+
+        // the rest of the slot runs in order on the same thread
+        let done: Vec<_> = rx.try_iter().collect();
+        assert_eq!(
+            done.iter().map(|(i, _)| *i).collect::<Vec<_>>(),
+            vec![0, 1, 3, 4, 5]
+        );
+        assert!(done.windows(2).all(|w| w[0].1 == w[1].1));
+
+        let mut reports = reports.lock().clone();
+        reports.sort();
+        assert_eq!(
+            reports,
+            vec![
+                (0, Some("42".to_owned()), None),
+                (1, Some("Ignore this panic, it must!".to_owned()), None),
+                (
+                    1,
+                    Some("Ignore this panic, it must!".to_owned()),
+                    Some("job 2".to_owned())
+                ),
+            ]
+        );
+        assert_eq!(pool.panic_count(), 3);
+
+        // a panicking handler doesn't count the job panic twice
+        let pool = ThreadPoolBuilder::new()
+            .num_threads(1)
+            .panic_handler(|_, _, _| panic!("Ignore this panic, it must!"))
+            .build();
+        pool.execute(0, || panic!("Ignore this panic, it must!"));
+        assert!(pool
+            .submit(0, || -> u32 { panic!("Ignore this panic, it must!") })
+            .join()
+            .is_err());
+        assert_eq!(pool.submit(0, || 1).join().unwrap(), 1);
+        pool.join();
+        assert_eq!(pool.panic_count(), 2);
+    }
+
     #[test]
     fn test_sync_shared_data() {
         fn assert_sync<T: Sync>() {}
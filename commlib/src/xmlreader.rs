@@ -66,7 +66,7 @@ impl XmlReader {
             Ok(content) => Self::read_content(&content),
             Err(e) => {
                 let errmsg = format!("parse xml file({:?}) error: {}.", path, e);
-                println!("{errmsg}");
+                log::error!("{errmsg}");
                 Err(errmsg)
             }
         }
@@ -86,7 +86,7 @@ impl XmlReader {
                     e,
                     content.len()
                 );
-                println!("{errmsg}");
+                log::error!("{errmsg}");
                 return Err(errmsg);
             }
         };
@@ -188,3 +188,27 @@ impl XmlReader {
         Some(list)
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_xml_reader_errors_are_logged() {
+        crate::test_logger::init();
+
+        let content = r#"<root><node key="a">1</node><node key="b">2</node></root>"#;
+        let reader = XmlReader::read_content(content).unwrap();
+        assert_eq!(reader.get_children(vec!["node"]).unwrap().len(), 2);
+        assert!(crate::test_logger::take().is_empty());
+
+        let err = XmlReader::read_content("<root>").unwrap_err();
+        let err2 = XmlReader::read_file(std::path::Path::new("no/such/file.xml")).unwrap_err();
+
+        let records = crate::test_logger::take();
+        assert_eq!(records.len(), 2);
+        assert!(records.iter().all(|r| r.level == log::Level::Error));
+        assert_eq!(records[0].message, err);
+        assert_eq!(records[1].message, err2);
+    }
+}